@@ -0,0 +1,373 @@
+//! A stand-in for BEYONDIO.dll that exports every `ldb*` function bound by `pangolin` with the
+//! C ABI.
+//!
+//! Every call is decoded independently of the `pangolin` crate, following the layouts described
+//! in the Beyond SDK documentation, and appended to a log as a single line of text. Tests load the
+//! library via `pangolin::LibraryLocator` and read the log back with `stubTakeLog` to check exactly
+//! what crossed the FFI boundary.
+//!
+//! The stub behaves as a started and ready instance of Beyond with 2 projectors and 4 zones.
+//!
+//! With the `core-only` feature the optional functions for projector images, projector events,
+//! timecode, MIDI, DMX, channels, the timeline and Kinect are not exported, as with older builds
+//! of BEYONDIO.dll. The `beyondio_stub_core` package builds the stub this way.
+
+// The exports follow the SDK's naming, and each pointer argument must be valid as the SDK
+// documents for the function of the same name.
+#![allow(non_snake_case, clippy::missing_safety_doc)]
+// The state and layouts of the optional functions go unused without them.
+#![cfg_attr(feature = "core-only", allow(dead_code))]
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::sync::Mutex;
+
+const DLL_VERSION: i32 = 100;
+const BEYOND_VERSION: i32 = 712;
+const PROJECTOR_COUNT: i32 = 2;
+const ZONE_COUNT: i32 = 4;
+
+/// The duration of the show loaded in the timeline, in milliseconds.
+const TIMELINE_DURATION: i32 = 60_000;
+
+/// The size of a point as laid out by the SDK's packed `TSdkImagePoint` record.
+const POINT_SIZE: usize = 20;
+
+/// The number of bytes read by `ldbSetDmx`.
+const DMX_SIZE: usize = 512;
+
+/// The number of nodes read by `ldbSetKinect`.
+const SKELETON_NODE_COUNT: usize = 20;
+
+/// The size of a node as laid out by the SDK's packed `TKPoint` record.
+const SKELETON_NODE_SIZE: usize = 13;
+
+struct State {
+    zone_images: Vec<String>,
+    projector_images: Vec<String>,
+    time_code: i32,
+    timeline_playing: bool,
+    timeline_pos: i32,
+    timeline_online: bool,
+    log: Vec<String>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    zone_images: Vec::new(),
+    projector_images: Vec::new(),
+    time_code: 0,
+    timeline_playing: false,
+    timeline_pos: 0,
+    timeline_online: false,
+    log: Vec::new(),
+});
+
+/// Log the call and return the given code.
+fn log(call: String, code: i32) -> i32 {
+    let mut state = STATE.lock().unwrap();
+    state.log.push(format!("{} -> {}", call, code));
+    code
+}
+
+unsafe fn name(image_name: *const c_char) -> String {
+    CStr::from_ptr(image_name).to_string_lossy().into_owned()
+}
+
+/// Copy the newline separated log into `buf` and clear it, returning the length of the log.
+///
+/// If `capacity` is too small nothing is copied or cleared, so that the call may be repeated with
+/// a large enough buffer.
+#[no_mangle]
+pub unsafe extern "C" fn stubTakeLog(buf: *mut u8, capacity: usize) -> usize {
+    let mut state = STATE.lock().unwrap();
+    let log = state.log.join("\n");
+    if log.len() <= capacity {
+        ptr::copy_nonoverlapping(log.as_ptr(), buf, log.len());
+        state.log.clear();
+    }
+    log.len()
+}
+
+#[no_mangle]
+pub extern "C" fn ldbCreate() -> i32 {
+    log("ldbCreate".to_string(), 1)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbDestroy() -> i32 {
+    {
+        let mut state = STATE.lock().unwrap();
+        state.zone_images.clear();
+        state.projector_images.clear();
+    }
+    log("ldbDestroy".to_string(), 1)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbBeyondExeStarted() -> i32 {
+    log("ldbBeyondExeStarted".to_string(), 1)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbBeyondExeReady() -> i32 {
+    log("ldbBeyondExeReady".to_string(), 1)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbEnableLaserOutput() -> i32 {
+    log("ldbEnableLaserOutput".to_string(), 1)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbDisableLaserOutput() -> i32 {
+    log("ldbDisableLaserOutput".to_string(), 1)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbBlackout() -> i32 {
+    log("ldbBlackout".to_string(), 1)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbGetDllVersion() -> i32 {
+    log("ldbGetDllVersion".to_string(), DLL_VERSION)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbGetBeyondVersion() -> i32 {
+    log("ldbGetBeyondVersion".to_string(), BEYOND_VERSION)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbGetProjectorCount() -> i32 {
+    log("ldbGetProjectorCount".to_string(), PROJECTOR_COUNT)
+}
+
+#[no_mangle]
+pub extern "C" fn ldbGetZoneCount() -> i32 {
+    log("ldbGetZoneCount".to_string(), ZONE_COUNT)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ldbCreateZoneImage(zone_index: i32, image_name: *const c_char) -> i32 {
+    let image_name = name(image_name);
+    {
+        let mut state = STATE.lock().unwrap();
+        if (0..ZONE_COUNT).contains(&zone_index) && !state.zone_images.contains(&image_name) {
+            state.zone_images.push(image_name.clone());
+        }
+    }
+    log(format!("ldbCreateZoneImage {} {}", zone_index, image_name), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub unsafe extern "C" fn ldbCreateProjectorImage(projector_index: i32,
+                                                 image_name: *const c_char) -> i32
+{
+    let image_name = name(image_name);
+    {
+        let mut state = STATE.lock().unwrap();
+        let index_valid = (0..PROJECTOR_COUNT).contains(&projector_index);
+        if index_valid && !state.projector_images.contains(&image_name) {
+            state.projector_images.push(image_name.clone());
+        }
+    }
+    log(format!("ldbCreateProjectorImage {} {}", projector_index, image_name), 1)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ldbDeleteZoneImage(image_name: *const c_char) -> i32 {
+    let image_name = name(image_name);
+    STATE.lock().unwrap().zone_images.retain(|name| *name != image_name);
+    log(format!("ldbDeleteZoneImage {}", image_name), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub unsafe extern "C" fn ldbDeleteProjectorImage(image_name: *const c_char) -> i32 {
+    let image_name = name(image_name);
+    STATE.lock().unwrap().projector_images.retain(|name| *name != image_name);
+    log(format!("ldbDeleteProjectorImage {}", image_name), 1)
+}
+
+/// Logs the image name, scan rate, zone indices (counting from 1) and each point as
+/// `(x, y, z, colour, rep_count, focus, status, zero)`.
+#[no_mangle]
+pub unsafe extern "C" fn ldbSendFrameToImage(image_name: *const c_char,
+                                             count: i32,
+                                             frame: *const c_void,
+                                             zones: *const c_void,
+                                             rate: i32) -> i32
+{
+    let image_name = name(image_name);
+
+    let zones = zones as *const u8;
+    let zones: Vec<u8> = (0..256)
+        .map(|i| *zones.offset(i))
+        .take_while(|&zone| zone != 0)
+        .collect();
+
+    let frame = frame as *const u8;
+    let points: Vec<String> = (0..count.max(0) as usize)
+        .map(|i| {
+            let point = frame.add(i * POINT_SIZE);
+            let f32_at = |offset: usize| ptr::read_unaligned(point.add(offset) as *const f32);
+            let colour = ptr::read_unaligned(point.add(12) as *const i32);
+            format!("({}, {}, {}, {:#08x}, {}, {}, {}, {})",
+                    f32_at(0), f32_at(4), f32_at(8), colour,
+                    *point.add(16), *point.add(17), *point.add(18), *point.add(19))
+        })
+        .collect();
+
+    let code = {
+        let state = STATE.lock().unwrap();
+        let known = state.zone_images.contains(&image_name)
+            || state.projector_images.contains(&image_name);
+        (known && (0..=8192).contains(&count)) as i32
+    };
+    let call = format!("ldbSendFrameToImage {} rate={} zones={:?} points=[{}]",
+                       image_name, rate, zones, points.join(", "));
+    log(call, code)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbGetTimeCode() -> i32 {
+    let time_code = STATE.lock().unwrap().time_code;
+    log("ldbGetTimeCode".to_string(), time_code)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbSetTimeCode(time_code: i32) -> i32 {
+    STATE.lock().unwrap().time_code = time_code;
+    log(format!("ldbSetTimeCode {}", time_code), time_code)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbSetMidiIn(command: u8, data1: u8, data2: u8, device_index: u8) -> i32 {
+    log(format!("ldbSetMidiIn {:#04x} {} {} {}", command, data1, data2, device_index), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbSetMidiOut(command: u8, data1: u8, data2: u8, device_index: u8) -> i32 {
+    log(format!("ldbSetMidiOut {:#04x} {} {} {}", command, data1, data2, device_index), 1)
+}
+
+/// Logs the input index and each non-zero channel of the universe as `(channel, value)`.
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub unsafe extern "C" fn ldbSetDmx(index: i32, data: *const c_void) -> i32 {
+    let data = data as *const u8;
+    let channels: Vec<String> = (0..DMX_SIZE)
+        .map(|i| (i, *data.add(i)))
+        .filter(|&(_, value)| value != 0)
+        .map(|(i, value)| format!("({}, {})", i, value))
+        .collect();
+    log(format!("ldbSetDmx {} channels=[{}]", index, channels.join(", ")), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub unsafe extern "C" fn ldbSetChannels(data: *const c_void, count: i32) -> i32 {
+    let data = data as *const u8;
+    let values: Vec<String> = (0..count.max(0) as usize)
+        .map(|i| ptr::read_unaligned(data.add(i * 4) as *const f32).to_string())
+        .collect();
+    log(format!("ldbSetChannels count={} values=[{}]", count, values.join(", ")), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelinePlay() -> i32 {
+    STATE.lock().unwrap().timeline_playing = true;
+    log("ldbTimelinePlay".to_string(), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelineStop() -> i32 {
+    STATE.lock().unwrap().timeline_playing = false;
+    log("ldbTimelineStop".to_string(), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelineGetPlaying() -> i32 {
+    let playing = STATE.lock().unwrap().timeline_playing;
+    log("ldbTimelineGetPlaying".to_string(), playing as i32)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelineSetPos(position: i32) -> i32 {
+    STATE.lock().unwrap().timeline_pos = position;
+    log(format!("ldbTimelineSetPos {}", position), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelineGetPos() -> i32 {
+    let position = STATE.lock().unwrap().timeline_pos;
+    log("ldbTimelineGetPos".to_string(), position)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelineGetDuration() -> i32 {
+    log("ldbTimelineGetDuration".to_string(), TIMELINE_DURATION)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelineSetOnline(online: i32) -> i32 {
+    STATE.lock().unwrap().timeline_online = online != 0;
+    log(format!("ldbTimelineSetOnline {}", online), 1)
+}
+
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub extern "C" fn ldbTimelineGetOnline() -> i32 {
+    let online = STATE.lock().unwrap().timeline_online;
+    log("ldbTimelineGetOnline".to_string(), online as i32)
+}
+
+/// Logs the skeleton index and each node as `(x, y, z, active)`.
+#[cfg(not(feature = "core-only"))]
+#[no_mangle]
+pub unsafe extern "C" fn ldbSetKinect(index: i32, data: *const c_void) -> i32 {
+    let data = data as *const u8;
+    let nodes: Vec<String> = (0..SKELETON_NODE_COUNT)
+        .map(|i| {
+            let node = data.add(i * SKELETON_NODE_SIZE);
+            let f32_at = |offset: usize| ptr::read_unaligned(node.add(offset) as *const f32);
+            format!("({}, {}, {}, {})", f32_at(0), f32_at(4), f32_at(8), *node.add(12))
+        })
+        .collect();
+    log(format!("ldbSetKinect {} nodes=[{}]", index, nodes.join(", ")), 1)
+}
+
+#[cfg(all(windows, not(feature = "core-only")))]
+extern "system" {
+    fn CreateEventW(attributes: *mut c_void,
+                    manual_reset: i32,
+                    initial_state: i32,
+                    name: *const u16) -> *mut c_void;
+}
+
+/// Returns a new event that is always signalled, so that waiting on it never blocks.
+#[cfg(all(windows, not(feature = "core-only")))]
+#[no_mangle]
+pub unsafe extern "C" fn ldbGetProjectorEvent(projector_index: i32) -> *mut c_void {
+    let handle = if (0..PROJECTOR_COUNT).contains(&projector_index) {
+        CreateEventW(ptr::null_mut(), 1, 1, ptr::null())
+    } else {
+        ptr::null_mut()
+    };
+    log(format!("ldbGetProjectorEvent {}", projector_index), !handle.is_null() as i32);
+    handle
+}
@@ -0,0 +1,227 @@
+//! Drives the real `Pangolin` FFI layer against the stub library.
+//!
+//! `ldbCreate` and `ldbDestroy` are process-wide, so everything happens within a single test per
+//! build of the stub.
+
+extern crate pangolin;
+
+use pangolin::{BeyondLaserPoint, Capabilities, Channels, DmxInput, DmxUniverse, Error, ImageName,
+               LaserBackend, Library, LibraryLocator, MidiDevicePair, MidiMessage, Pangolin,
+               Result, ScanRate, Skeleton, SkeletonNode, Symbol, Timecode, ZoneSet,
+               SKELETON_NODE_COUNT};
+use std::env;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+/// The stub library with the given name, which cargo builds alongside this test in the `deps`
+/// directory and also copies into the target directory when building the workspace.
+fn stub_path(name: &str) -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let deps_dir = exe.parent().unwrap();
+    let file_name = format!("{}{}{}", env::consts::DLL_PREFIX, name, env::consts::DLL_SUFFIX);
+    let path = deps_dir.join(&file_name);
+    if path.exists() {
+        return path;
+    }
+    deps_dir.parent().unwrap().join(file_name)
+}
+
+/// Take the lines logged by the stub since the last call.
+fn take_log(lib: &Library) -> Vec<String> {
+    unsafe {
+        let take: Symbol<unsafe extern "C" fn(*mut u8, usize) -> usize> =
+            lib.get(b"stubTakeLog\0").unwrap();
+        let mut buf = Vec::new();
+        let len = take(buf.as_mut_ptr(), 0);
+        buf.resize(len, 0);
+        assert_eq!(take(buf.as_mut_ptr(), len), len);
+        String::from_utf8(buf).unwrap().lines().map(String::from).collect()
+    }
+}
+
+/// Load the stub library with the given name, along with a second handle to the same instance for
+/// reading its log.
+fn load_stub(name: &str) -> (Pangolin, Library) {
+    let locator = LibraryLocator::new()
+        .path(stub_path(name))
+        .env_var(None)
+        .install_dirs(vec![])
+        .search_exe_folders(false);
+    let pangolin = Pangolin::load_with(&locator).unwrap();
+    // Loading the library again shares the instance loaded by `pangolin`.
+    let stub = Library::new(stub_path(name)).unwrap();
+    assert_eq!(take_log(&stub), vec!["ldbCreate -> 1"]);
+    (pangolin, stub)
+}
+
+fn assert_unsupported<T: Debug>(result: Result<T>, function: &str) {
+    match result {
+        Err(Error::Unsupported(name)) if name == function => (),
+        result => panic!("expected {} to be unsupported, found {:?}", function, result),
+    }
+}
+
+#[test]
+fn test_pangolin_ffi() {
+    let (pangolin, stub) = load_stub("beyondio_stub");
+
+    assert_eq!(pangolin.capabilities(), Capabilities {
+        projector_images: true,
+        projector_events: cfg!(windows),
+        timecode: true,
+        midi: true,
+        dmx: true,
+        channels: true,
+        kinect: true,
+        timeline: true,
+    });
+
+    assert!(pangolin.beyond_exe_ready().unwrap());
+    assert_eq!(pangolin.get_zone_count().unwrap(), 4);
+    assert_eq!(take_log(&stub), vec!["ldbBeyondExeReady -> 1", "ldbGetZoneCount -> 4"]);
+
+    {
+        let output1 = ImageName::new("/output1").unwrap();
+        let image = pangolin.create_zone_image(1, output1).unwrap();
+        let mut point = BeyondLaserPoint::from_units(1.5, -2.0, 3.0, 0x12, 0x34, 0x56);
+        point.set_rep_count(2).set_focus(3).set_status(4);
+        let zones = ZoneSet::from_indices(&[2, 0]).unwrap();
+        let scan_rate = ScanRate::PointsPerSecond(30_000);
+        image.send_frame(&[point, point], &zones, scan_rate).unwrap();
+        assert_eq!(take_log(&stub), vec![
+            "ldbGetZoneCount -> 4",
+            "ldbCreateZoneImage 1 /output1 -> 1",
+            "ldbSendFrameToImage /output1 rate=-30000 zones=[3, 1] points=[\
+             (1.5, -2, 3, 0x563412, 2, 3, 4, 0), (1.5, -2, 3, 0x563412, 2, 3, 4, 0)] -> 1",
+        ]);
+
+        // Frames for images that don't exist are rejected by the stub.
+        let missing = ImageName::new("/missing").unwrap();
+        assert!(pangolin.send_frame_to_image(&missing, &[], &zones, scan_rate).is_err());
+        take_log(&stub);
+    }
+    assert_eq!(take_log(&stub), vec!["ldbDeleteZoneImage /output1 -> 1"]);
+
+    let timecode = Timecode::from_millis(1_500).unwrap();
+    pangolin.set_time_code(timecode).unwrap();
+    assert_eq!(pangolin.get_time_code().unwrap(), timecode);
+    assert_eq!(take_log(&stub), vec!["ldbSetTimeCode 1500 -> 1500", "ldbGetTimeCode -> 1500"]);
+
+    let note_on = MidiMessage::note_on(1, 60, 127).unwrap();
+    pangolin.set_midi_in(note_on, MidiDevicePair::new(2).unwrap()).unwrap();
+    let control_change = MidiMessage::control_change(15, 7, 100).unwrap();
+    pangolin.set_midi_out(control_change, MidiDevicePair::new(3).unwrap()).unwrap();
+    assert_eq!(take_log(&stub), vec![
+        "ldbSetMidiIn 0x91 60 127 2 -> 1",
+        "ldbSetMidiOut 0xbf 7 100 3 -> 1",
+    ]);
+
+    // The stub reads all 512 bytes, so the last channel must arrive too.
+    let mut universe = DmxUniverse::new();
+    universe.0[0] = 255;
+    universe.0[100] = 7;
+    universe.0[511] = 1;
+    pangolin.set_dmx(DmxInput::new(3).unwrap(), &universe).unwrap();
+    assert_eq!(take_log(&stub), vec!["ldbSetDmx 3 channels=[(0, 255), (100, 7), (511, 1)] -> 1"]);
+
+    // Channels that are not set are sent as -1, which Beyond leaves unchanged.
+    let mut channels = Channels::new();
+    channels.set(0, 0.25).unwrap().set(3, 1.0).unwrap();
+    pangolin.set_channels(&channels).unwrap();
+    assert_eq!(take_log(&stub), vec!["ldbSetChannels count=4 values=[0.25, -1, -1, 1] -> 1"]);
+
+    // Each node is decoded at the packed 13-byte stride, so a padded layout would be misread.
+    let mut skeleton = Skeleton::default();
+    for (i, node) in skeleton.0.iter_mut().enumerate() {
+        *node = SkeletonNode::new(i as f32, -0.5, 2.0 + i as f32, i % 3 == 0);
+    }
+    pangolin.set_skeleton(1, &skeleton).unwrap();
+    let nodes: Vec<String> = (0..SKELETON_NODE_COUNT)
+        .map(|i| format!("({}, -0.5, {}, {})", i, 2 + i, (i % 3 == 0) as u8))
+        .collect();
+    assert_eq!(take_log(&stub), vec![format!("ldbSetKinect 1 nodes=[{}] -> 1", nodes.join(", "))]);
+
+    let timeline = pangolin.timeline();
+    timeline.play().unwrap();
+    assert!(timeline.is_playing().unwrap());
+    timeline.seek(Timecode::from_millis(2_500).unwrap()).unwrap();
+    assert_eq!(timeline.position().unwrap(), Timecode::from_millis(2_500).unwrap());
+    assert_eq!(timeline.duration().unwrap(), Timecode::from_millis(60_000).unwrap());
+    timeline.set_online(true).unwrap();
+    assert!(timeline.is_online().unwrap());
+    timeline.stop().unwrap();
+    assert!(!timeline.is_playing().unwrap());
+    assert_eq!(take_log(&stub), vec![
+        "ldbTimelinePlay -> 1",
+        "ldbTimelineGetPlaying -> 1",
+        "ldbTimelineSetPos 2500 -> 1",
+        "ldbTimelineGetPos -> 2500",
+        "ldbTimelineGetDuration -> 60000",
+        "ldbTimelineSetOnline 1 -> 1",
+        "ldbTimelineGetOnline -> 1",
+        "ldbTimelineStop -> 1",
+        "ldbTimelineGetPlaying -> 0",
+    ]);
+
+    #[cfg(windows)]
+    {
+        pangolin.projector_event(1).unwrap();
+        assert_eq!(take_log(&stub), vec!["ldbGetProjectorEvent 1 -> 1"]);
+    }
+
+    drop(pangolin);
+    assert_eq!(take_log(&stub), vec!["ldbDisableLaserOutput -> 1", "ldbDestroy -> 1"]);
+}
+
+#[test]
+fn test_pangolin_ffi_core_only() {
+    let (pangolin, stub) = load_stub("beyondio_stub_core");
+    assert_eq!(pangolin.capabilities(), Capabilities::default());
+
+    // The core functions still work.
+    {
+        let output1 = ImageName::new("/output1").unwrap();
+        let image = pangolin.create_zone_image(0, output1).unwrap();
+        let zones = ZoneSet::single(1).unwrap();
+        image.send_frame(&[], &zones, ScanRate::default()).unwrap();
+    }
+    assert_eq!(take_log(&stub), vec![
+        "ldbGetZoneCount -> 4",
+        "ldbCreateZoneImage 0 /output1 -> 1",
+        "ldbSendFrameToImage /output1 rate=100 zones=[2] points=[] -> 1",
+        "ldbDeleteZoneImage /output1 -> 1",
+    ]);
+
+    // Every optional function fails without calling into the library.
+    let image_name = ImageName::new("/projector1").unwrap();
+    assert_unsupported(pangolin.insert_projector_image(0, &image_name), "ldbCreateProjectorImage");
+    assert_unsupported(pangolin.delete_projector_image(&image_name), "ldbDeleteProjectorImage");
+    let timecode = Timecode::from_millis(1_500).unwrap();
+    assert_unsupported(pangolin.set_time_code(timecode), "ldbSetTimeCode");
+    assert_unsupported(pangolin.get_time_code(), "ldbGetTimeCode");
+    let message = MidiMessage::note_on(0, 60, 127).unwrap();
+    let device = MidiDevicePair::new(0).unwrap();
+    assert_unsupported(pangolin.set_midi_in(message, device), "ldbSetMidiIn");
+    assert_unsupported(pangolin.set_midi_out(message, device), "ldbSetMidiOut");
+    let input = DmxInput::new(0).unwrap();
+    assert_unsupported(pangolin.set_dmx(input, &DmxUniverse::new()), "ldbSetDmx");
+    let mut channels = Channels::new();
+    channels.set(0, 1.0).unwrap();
+    assert_unsupported(pangolin.set_channels(&channels), "ldbSetChannels");
+    assert_unsupported(pangolin.set_skeleton(0, &Skeleton::default()), "ldbSetKinect");
+    let timeline = pangolin.timeline();
+    assert_unsupported(timeline.play(), "ldbTimelinePlay");
+    assert_unsupported(timeline.stop(), "ldbTimelineStop");
+    assert_unsupported(timeline.is_playing(), "ldbTimelineGetPlaying");
+    assert_unsupported(timeline.seek(timecode), "ldbTimelineSetPos");
+    assert_unsupported(timeline.position(), "ldbTimelineGetPos");
+    assert_unsupported(timeline.duration(), "ldbTimelineGetDuration");
+    assert_unsupported(timeline.set_online(true), "ldbTimelineSetOnline");
+    assert_unsupported(timeline.is_online(), "ldbTimelineGetOnline");
+    #[cfg(windows)]
+    assert_unsupported(pangolin.projector_event(0).map(|_| ()), "ldbGetProjectorEvent");
+    assert_eq!(take_log(&stub), Vec::<String>::new());
+
+    drop(pangolin);
+    assert_eq!(take_log(&stub), vec!["ldbDisableLaserOutput -> 1", "ldbDestroy -> 1"]);
+}
@@ -0,0 +1,202 @@
+use {BeyondLaserPoint, Result, ScanRate, MAX_NUM_POINTS};
+use reduce::{self, FrameReducer, ReducerChain};
+use image::{Image, ImageKind, ImageName};
+use info::BeyondInfo;
+use zone::{ZoneArray, ZoneSet};
+use std::sync::Arc;
+
+/// The set of operations the bridge needs from a Beyond instance.
+///
+/// `Pangolin` implements this by calling into BEYONDIO.dll. Other implementations allow the
+/// OSC-to-laser pipeline to be built and exercised on machines without Beyond or Windows.
+pub trait LaserBackend {
+    /// Whether or not BEYOND.EXE has started executing.
+    fn beyond_exe_started(&self) -> Result<bool>;
+
+    /// Whether or not the Beyond SDK is initialised and ready to be used.
+    fn beyond_exe_ready(&self) -> Result<bool>;
+
+    /// Equivalent to clicking the "Enable Laser Output" button.
+    fn enable_laser_output(&self) -> Result<()>;
+
+    /// Equivalent to clicking the "Disable Laser Output" button.
+    fn disable_laser_output(&self) -> Result<()>;
+
+    /// Equivalent to clicking the "Blackout" button. Stops all players and clears SDK images.
+    fn blackout(&self) -> Result<()>;
+
+    fn get_dll_version(&self) -> Result<i32>;
+
+    /// The build number of Beyond, or zero if Beyond is not started.
+    fn get_beyond_version(&self) -> Result<i32>;
+
+    /// The number of projectors in Beyond, or zero if Beyond is not active.
+    fn get_projector_count(&self) -> Result<i32>;
+
+    /// The number of projection zones in Beyond, or zero if Beyond is not active.
+    fn get_zone_count(&self) -> Result<i32>;
+
+    /// Query the versions, state and counts of Beyond in a single call.
+    fn beyond_info(&self) -> Result<BeyondInfo> {
+        BeyondInfo::query(self)
+    }
+
+    /// Add a new SDK image to the image-list of the given projection zone, as
+    /// `ldbCreateZoneImage`. The image is not deleted automatically, prefer `create_zone_image`.
+    ///
+    /// Beyond ignores the call if an image with the same name already exists in any zone. The SDK
+    /// doesn't document the value returned by `ldbCreateZoneImage`, so `Pangolin` only returns an
+    /// error if the call could not be made at all.
+    fn insert_zone_image(&self, zone_index: i32, image_name: &ImageName) -> Result<()>;
+
+    /// Add a new SDK image to the image-list of the given projector, as
+    /// `ldbCreateProjectorImage`. The image is not deleted automatically, prefer
+    /// `create_projector_image`.
+    ///
+    /// Beyond ignores the call if an image with the same name already exists in any projector.
+    fn insert_projector_image(&self, projector_index: i32, image_name: &ImageName) -> Result<()>;
+
+    /// Remove the SDK image with the given name from its projection zone.
+    fn delete_zone_image(&self, image_name: &ImageName) -> Result<()>;
+
+    /// Remove the SDK image with the given name from its projector.
+    fn delete_projector_image(&self, image_name: &ImageName) -> Result<()>;
+
+    /// Create an SDK image within the given projection zone.
+    ///
+    /// The image is deleted from Beyond when the returned `Image` is dropped. The zone count is
+    /// queried first, and frames sent via the `Image` are checked against it.
+    fn create_zone_image<'a>(&'a self, zone_index: i32, image_name: ImageName)
+        -> Result<Image<'a, Self>>
+    {
+        let zone_count = self.get_zone_count()?;
+        self.insert_zone_image(zone_index, &image_name)?;
+        Ok(Image::new(self, ImageKind::Zone, zone_index, image_name, zone_count))
+    }
+
+    /// Create an SDK image within the given projector. See `create_zone_image`.
+    ///
+    /// The image is deleted from Beyond when the returned `Image` is dropped.
+    fn create_projector_image<'a>(&'a self, projector_index: i32, image_name: ImageName)
+        -> Result<Image<'a, Self>>
+    {
+        let zone_count = self.get_zone_count()?;
+        self.insert_projector_image(projector_index, &image_name)?;
+        Ok(Image::new(self, ImageKind::Projector, projector_index, image_name, zone_count))
+    }
+
+    /// Deliver a frame to the SDK image with the given name, exactly as `ldbSendFrameToImage`.
+    ///
+    /// `laser_points` must not contain more than `MAX_NUM_POINTS`. Prefer `send_frame_to_image`,
+    /// which reduces larger frames to fit.
+    ///
+    /// `scan_rate` is the raw `ARate` argument, as produced by `ScanRate::to_sdk`.
+    fn send_frame(&self,
+                  image_name: &ImageName,
+                  laser_points: &[BeyondLaserPoint],
+                  zone_array: &ZoneArray,
+                  scan_rate: i32) -> Result<()>;
+
+    /// Deliver a frame to the SDK image with the given name, addressed to each of `zones` in order.
+    ///
+    /// Frames with more than `MAX_NUM_POINTS` are reduced with the default `ReducerChain`.
+    ///
+    /// Returns an error if `scan_rate` is invalid. The zones are not checked against Beyond's zone
+    /// count, which would cost an extra call per frame. Check them once with `ZoneSet::validate`,
+    /// or send via an `Image`, which checks them against the zone count cached at creation.
+    fn send_frame_to_image(&self,
+                           image_name: &ImageName,
+                           laser_points: &[BeyondLaserPoint],
+                           zones: &ZoneSet,
+                           scan_rate: ScanRate) -> Result<()>
+    {
+        if laser_points.len() > MAX_NUM_POINTS {
+            let reducer = ReducerChain::default();
+            return self.send_frame_to_image_with(&reducer, image_name, laser_points, zones,
+                                                 scan_rate);
+        }
+        let scan_rate = scan_rate.to_sdk()?;
+        let zone_array = ZoneArray::new(zones);
+        self.send_frame(image_name, laser_points, &zone_array, scan_rate)
+    }
+
+    /// As `send_frame_to_image`, but frames with more than `MAX_NUM_POINTS` are reduced with the
+    /// given reducer.
+    fn send_frame_to_image_with(&self,
+                                reducer: &dyn FrameReducer,
+                                image_name: &ImageName,
+                                laser_points: &[BeyondLaserPoint],
+                                zones: &ZoneSet,
+                                scan_rate: ScanRate) -> Result<()>
+    {
+        let scan_rate = scan_rate.to_sdk()?;
+        // Make sure we dont exceed the max num of points for Pangolin
+        let laser_points = reduce::reduce_frame(reducer, laser_points, MAX_NUM_POINTS);
+        let zone_array = ZoneArray::new(zones);
+        self.send_frame(image_name, &laser_points, &zone_array, scan_rate)
+    }
+}
+
+/// Allows a backend shared between threads to be wrapped, e.g. by a `TraceRecorder`.
+impl<B: ?Sized + LaserBackend> LaserBackend for Arc<B> {
+    fn beyond_exe_started(&self) -> Result<bool> {
+        (**self).beyond_exe_started()
+    }
+
+    fn beyond_exe_ready(&self) -> Result<bool> {
+        (**self).beyond_exe_ready()
+    }
+
+    fn enable_laser_output(&self) -> Result<()> {
+        (**self).enable_laser_output()
+    }
+
+    fn disable_laser_output(&self) -> Result<()> {
+        (**self).disable_laser_output()
+    }
+
+    fn blackout(&self) -> Result<()> {
+        (**self).blackout()
+    }
+
+    fn get_dll_version(&self) -> Result<i32> {
+        (**self).get_dll_version()
+    }
+
+    fn get_beyond_version(&self) -> Result<i32> {
+        (**self).get_beyond_version()
+    }
+
+    fn get_projector_count(&self) -> Result<i32> {
+        (**self).get_projector_count()
+    }
+
+    fn get_zone_count(&self) -> Result<i32> {
+        (**self).get_zone_count()
+    }
+
+    fn insert_zone_image(&self, zone_index: i32, image_name: &ImageName) -> Result<()> {
+        (**self).insert_zone_image(zone_index, image_name)
+    }
+
+    fn insert_projector_image(&self, projector_index: i32, image_name: &ImageName) -> Result<()> {
+        (**self).insert_projector_image(projector_index, image_name)
+    }
+
+    fn delete_zone_image(&self, image_name: &ImageName) -> Result<()> {
+        (**self).delete_zone_image(image_name)
+    }
+
+    fn delete_projector_image(&self, image_name: &ImageName) -> Result<()> {
+        (**self).delete_projector_image(image_name)
+    }
+
+    fn send_frame(&self,
+                  image_name: &ImageName,
+                  laser_points: &[BeyondLaserPoint],
+                  zone_array: &ZoneArray,
+                  scan_rate: i32) -> Result<()>
+    {
+        (**self).send_frame(image_name, laser_points, zone_array, scan_rate)
+    }
+}
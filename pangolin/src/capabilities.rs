@@ -0,0 +1,59 @@
+use Pangolin;
+
+/// The optional groups of SDK functions exported by the loaded BEYONDIO.dll.
+///
+/// Older builds of the DLL only provide the core zone image API. Each field is `true` only if
+/// every function within the group was found.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    /// `ldbCreateProjectorImage` and `ldbDeleteProjectorImage`.
+    pub projector_images: bool,
+    /// `ldbGetProjectorEvent`. Only ever `true` on Windows.
+    pub projector_events: bool,
+    /// `ldbGetTimeCode` and `ldbSetTimeCode`.
+    pub timecode: bool,
+    /// `ldbSetMidiIn` and `ldbSetMidiOut`.
+    pub midi: bool,
+    /// `ldbSetDmx`.
+    pub dmx: bool,
+    /// `ldbSetChannels`.
+    pub channels: bool,
+    /// `ldbSetKinect`.
+    pub kinect: bool,
+    /// All `ldbTimeline*` functions.
+    pub timeline: bool,
+}
+
+impl Pangolin {
+    /// Which of the optional SDK functions are available.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            projector_images: self.ldb_create_projector_image.is_some()
+                && self.ldb_delete_projector_image.is_some(),
+            projector_events: self.has_projector_events(),
+            timecode: self.ldb_get_time_code.is_some() && self.ldb_set_time_code.is_some(),
+            midi: self.ldb_set_midi_in.is_some() && self.ldb_set_midi_out.is_some(),
+            dmx: self.ldb_set_dmx.is_some(),
+            channels: self.ldb_set_channels.is_some(),
+            kinect: self.ldb_set_kinect.is_some(),
+            timeline: self.ldb_timeline_play.is_some()
+                && self.ldb_timeline_stop.is_some()
+                && self.ldb_timeline_get_playing.is_some()
+                && self.ldb_timeline_set_pos.is_some()
+                && self.ldb_timeline_get_pos.is_some()
+                && self.ldb_timeline_get_duration.is_some()
+                && self.ldb_timeline_set_online.is_some()
+                && self.ldb_timeline_get_online.is_some(),
+        }
+    }
+
+    #[cfg(windows)]
+    fn has_projector_events(&self) -> bool {
+        self.ldb_get_projector_event.is_some()
+    }
+
+    #[cfg(not(windows))]
+    fn has_projector_events(&self) -> bool {
+        false
+    }
+}
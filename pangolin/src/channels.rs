@@ -0,0 +1,104 @@
+use {supported, Error, Pangolin, Result};
+use std::os::raw::c_void;
+
+/// The maximum number of channels that may be supplied to `ldbSetChannels`.
+pub const MAX_CHANNELS: usize = 255;
+
+/// The value that tells Beyond to leave a channel unchanged.
+const UNCHANGED: f32 = -1.0;
+
+/// A sparse set of values for Beyond's normalised channels.
+///
+/// Channels are addressed by their zero-based position within the array passed to
+/// `ldbSetChannels`. Any channel that has not been set is left unchanged by Beyond.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Channels {
+    values: Vec<f32>,
+}
+
+impl Channels {
+    pub fn new() -> Self {
+        Channels { values: vec![] }
+    }
+
+    /// Set the channel at `index` to `value`, clamped to the range `0.0..=1.0`.
+    ///
+    /// Returns an error if `index` is not less than `MAX_CHANNELS`.
+    pub fn set(&mut self, index: usize, value: f32) -> Result<&mut Self> {
+        if index >= MAX_CHANNELS {
+            return Err(Error::InvalidChannelIndex(index));
+        }
+        if self.values.len() <= index {
+            self.values.resize(index + 1, UNCHANGED);
+        }
+        self.values[index] = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+        Ok(self)
+    }
+
+    /// Leave the channel at `index` unchanged.
+    pub fn unset(&mut self, index: usize) -> &mut Self {
+        if index < self.values.len() {
+            self.values[index] = UNCHANGED;
+        }
+        while self.values.last() == Some(&UNCHANGED) {
+            self.values.pop();
+        }
+        self
+    }
+
+    /// The value of the channel at `index`, or `None` if it is left unchanged.
+    pub fn get(&self, index: usize) -> Option<f32> {
+        match self.values.get(index) {
+            Some(&value) if value != UNCHANGED => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The array passed to `ldbSetChannels`, where -1 indicates an unchanged channel.
+    pub fn as_slice(&self) -> &[f32] {
+        &self.values
+    }
+}
+
+impl Pangolin {
+    /// Update the channels set within `channels`, leaving all other channels unchanged.
+    ///
+    /// Returns an error without calling into the DLL if no channels are set. The SDK doesn't
+    /// document the value returned by `ldbSetChannels`, so it is ignored.
+    pub fn set_channels(&self, channels: &Channels) -> Result<()> {
+        let count = channels.values.len();
+        if count == 0 {
+            return Err(Error::InvalidChannelCount(count));
+        }
+        let data = channels.values.as_ptr() as *const c_void;
+        let function = supported(self.ldb_set_channels, "ldbSetChannels")?;
+        unsafe {
+            function(data, count as i32);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_channels() {
+    let mut channels = Channels::new();
+    channels.set(0, 0.5).unwrap().set(3, 2.0).unwrap().set(4, -3.0).unwrap();
+    assert_eq!(channels.as_slice(), &[0.5, -1.0, -1.0, 1.0, 0.0]);
+    assert_eq!(channels.get(1), None);
+    assert_eq!(channels.get(3), Some(1.0));
+    channels.unset(4).unset(3);
+    assert_eq!(channels.as_slice(), &[0.5]);
+}
+
+#[test]
+fn test_channels_reject_out_of_range_index() {
+    let mut channels = Channels::new();
+    assert!(channels.set(MAX_CHANNELS - 1, 0.5).is_ok());
+    for &index in &[MAX_CHANNELS, 1 << 40, usize::MAX] {
+        match channels.set(index, 0.5) {
+            Err(Error::InvalidChannelIndex(i)) => assert_eq!(i, index),
+            result => panic!("expected an invalid channel index, found {:?}", result),
+        }
+    }
+    assert_eq!(channels.as_slice().len(), MAX_CHANNELS);
+}
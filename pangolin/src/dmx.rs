@@ -0,0 +1,74 @@
+use {supported, Error, Pangolin, Result};
+use std::os::raw::c_void;
+
+/// The number of channels within a single DMX universe.
+pub const DMX_UNIVERSE_CHANNELS: usize = 512;
+
+/// A full universe of DMX channel values.
+///
+/// `ldbSetDmx` always reads exactly 512 bytes, so a complete universe must be supplied.
+#[derive(Clone)]
+pub struct DmxUniverse(pub [u8; DMX_UNIVERSE_CHANNELS]);
+
+/// One of Beyond's four emulated DMX inputs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DmxInput {
+    index: i32,
+}
+
+impl DmxUniverse {
+    /// A universe with all channels set to zero.
+    pub fn new() -> Self {
+        DmxUniverse([0; DMX_UNIVERSE_CHANNELS])
+    }
+}
+
+impl Default for DmxUniverse {
+    fn default() -> Self {
+        DmxUniverse::new()
+    }
+}
+
+impl DmxInput {
+    /// The number of DMX inputs supported by Beyond.
+    pub const COUNT: i32 = 4;
+
+    /// Returns an error if `index` is not in the range `0..DmxInput::COUNT`.
+    pub fn new(index: i32) -> Result<Self> {
+        if !(0..DmxInput::COUNT).contains(&index) {
+            return Err(Error::InvalidDmxInput(index));
+        }
+        Ok(DmxInput { index })
+    }
+
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+}
+
+impl Pangolin {
+    /// Write a universe into the input buffer of the given DMX input, emulating DMX IN.
+    ///
+    /// The SDK doesn't document the value returned by `ldbSetDmx`, so it is ignored and this only
+    /// fails if the function is unsupported.
+    pub fn set_dmx(&self, input: DmxInput, universe: &DmxUniverse) -> Result<()> {
+        let data = universe.0.as_ptr() as *const c_void;
+        let function = supported(self.ldb_set_dmx, "ldbSetDmx")?;
+        unsafe {
+            function(input.index, data);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_dmx_input() {
+    assert_eq!(DmxInput::new(0).unwrap().index(), 0);
+    assert_eq!(DmxInput::new(DmxInput::COUNT - 1).unwrap().index(), DmxInput::COUNT - 1);
+    for &index in &[-1, DmxInput::COUNT, i32::MAX] {
+        match DmxInput::new(index) {
+            Err(Error::InvalidDmxInput(i)) => assert_eq!(i, index),
+            result => panic!("expected an invalid DMX input, found {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use {DmxInput, LocatorAttempt, MidiDevicePair, MAX_CHANNELS};
+use std::{error, fmt, io};
+use std::time::Duration;
+
+/// Errors that may occur while loading or calling into BEYONDIO.dll.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to load BEYONDIO.dll or to resolve one of its functions.
+    Library(io::Error),
+    /// BEYONDIO.dll could not be loaded from any of the locations that were tried.
+    LibraryNotFound(Vec<LocatorAttempt>),
+    /// `ldbCreate` failed to initialise the DLL.
+    Create { code: i32 },
+    /// An SDK function reported that it failed.
+    Failed { function: &'static str, code: i32 },
+    /// An SDK function returned a value that the SDK does not document.
+    UnexpectedReturn { function: &'static str, code: i32 },
+    /// An image name was empty or contained a non-ASCII or NUL character.
+    InvalidImageName(String),
+    /// The number of channels supplied to `ldbSetChannels` was not in the range 1 to
+    /// `MAX_CHANNELS`.
+    InvalidChannelCount(usize),
+    /// A channel index was not less than `MAX_CHANNELS`.
+    InvalidChannelIndex(usize),
+    /// A MIDI device pair index was not less than `MidiDevicePair::COUNT`.
+    InvalidMidiDevicePair(u8),
+    /// A MIDI command byte was not a status byte, i.e. was less than `0x80`.
+    InvalidMidiCommand(u8),
+    /// A MIDI data byte was not less than `0x80`.
+    InvalidMidiData(u8),
+    /// A MIDI channel was not less than 16.
+    InvalidMidiChannel(u8),
+    /// A DMX input index was not in the range `0..DmxInput::COUNT`.
+    InvalidDmxInput(i32),
+    /// A timecode was greater than `Timecode::MAX`.
+    InvalidTimecode(Duration),
+    /// A skeleton index other than 0 or 1 was supplied to `ldbSetKinect`.
+    InvalidSkeletonIndex(i32),
+    /// A zone index was not less than the number of zones in Beyond, or than `MAX_ZONES`.
+    InvalidZoneIndex { index: u8, zone_count: i32 },
+    /// A zone was added to a `ZoneSet` that already contained it.
+    DuplicateZoneIndex(u8),
+    /// A scan rate was zero, out of range or could not be parsed.
+    InvalidScanRate(String),
+    /// Failed to read or write a call trace.
+    Trace(io::Error),
+    /// BEYONDIO.dll or Beyond is older than the minimum supported version.
+    UnsupportedVersion { component: &'static str, version: i32, minimum: i32 },
+    /// Beyond has fewer projectors or zones than the configured outputs need.
+    InsufficientOutputs { kind: &'static str, required: i32, available: i32 },
+    /// The loaded BEYONDIO.dll does not export the named SDK function.
+    Unsupported(&'static str),
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Interpret the return value of an SDK function that returns 1 on success and 0 on failure.
+pub fn check(function: &'static str, code: i32) -> Result<()> {
+    match code {
+        1 => Ok(()),
+        0 => Err(Error::Failed { function, code }),
+        code => Err(Error::UnexpectedReturn { function, code }),
+    }
+}
+
+/// Interpret the return value of an SDK function that returns 1 for true and 0 for false.
+pub fn check_bool(function: &'static str, code: i32) -> Result<bool> {
+    match code {
+        0 => Ok(false),
+        1 => Ok(true),
+        code => Err(Error::UnexpectedReturn { function, code }),
+    }
+}
+
+/// Interpret the return value of an SDK function that returns a count.
+pub fn check_count(function: &'static str, code: i32) -> Result<i32> {
+    if code < 0 {
+        return Err(Error::UnexpectedReturn { function, code });
+    }
+    Ok(code)
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Library(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Library(ref err) => write!(f, "failed to load BEYONDIO.dll: {}", err),
+            Error::LibraryNotFound(ref attempts) => {
+                write!(f, "failed to find BEYONDIO.dll, tried:")?;
+                for attempt in attempts {
+                    write!(f, "\n  {}", attempt)?;
+                }
+                Ok(())
+            },
+            Error::Create { code } => write!(f, "ldbCreate failed with code {}", code),
+            Error::Failed { function, code } => write!(f, "{} failed with code {}", function, code),
+            Error::UnexpectedReturn { function, code } =>
+                write!(f, "{} returned an undocumented value {}", function, code),
+            Error::InvalidImageName(ref name) =>
+                write!(f, "invalid image name {:?}: must be non-empty ASCII without NUL", name),
+            Error::InvalidChannelCount(count) =>
+                write!(f, "invalid channel count {}: must be between 1 and {}", count, MAX_CHANNELS),
+            Error::InvalidChannelIndex(index) =>
+                write!(f, "invalid channel index {}: must be less than {}", index, MAX_CHANNELS),
+            Error::InvalidMidiDevicePair(index) =>
+                write!(f, "invalid MIDI device pair {}: must be less than {}",
+                       index, MidiDevicePair::COUNT),
+            Error::InvalidMidiCommand(command) =>
+                write!(f, "invalid MIDI command {:#04x}: must be at least 0x80", command),
+            Error::InvalidMidiData(data) =>
+                write!(f, "invalid MIDI data byte {:#04x}: must be less than 0x80", data),
+            Error::InvalidMidiChannel(channel) =>
+                write!(f, "invalid MIDI channel {}: must be less than 16", channel),
+            Error::InvalidDmxInput(index) =>
+                write!(f, "invalid DMX input {}: must be 0 to {}", index, DmxInput::COUNT - 1),
+            Error::InvalidTimecode(duration) =>
+                write!(f, "invalid timecode {:?}: must be at most {}ms", duration, i32::MAX),
+            Error::InvalidSkeletonIndex(index) =>
+                write!(f, "invalid skeleton index {}: must be 0 or 1", index),
+            Error::InvalidZoneIndex { index, zone_count } =>
+                write!(f, "invalid zone index {}: must be less than {}", index, zone_count),
+            Error::DuplicateZoneIndex(index) =>
+                write!(f, "zone index {} appears more than once", index),
+            Error::InvalidScanRate(ref rate) =>
+                write!(f, "invalid scan rate {:?}: expected e.g. \"100%\" or \"30000pps\"", rate),
+            Error::Trace(ref err) => write!(f, "failed to read or write trace: {}", err),
+            Error::UnsupportedVersion { component, version, minimum } =>
+                write!(f, "{} version {} is not supported: must be at least {}",
+                       component, version, minimum),
+            Error::InsufficientOutputs { kind, required, available } =>
+                write!(f, "{} {} required but Beyond only has {}", required, kind, available),
+            Error::Unsupported(function) =>
+                write!(f, "{} is not supported by this version of BEYONDIO.dll", function),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Library(ref err) | Error::Trace(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
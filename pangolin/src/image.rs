@@ -0,0 +1,139 @@
+use {BeyondLaserPoint, Error, FrameReducer, LaserBackend, Result, ScanRate, ZoneSet};
+use std::ffi::CString;
+use std::fmt;
+
+/// The name of an SDK image.
+///
+/// Beyond identifies SDK images by a zero terminated, non-unicode ANSI string which it also uses
+/// as the name of the image's window. An `ImageName` is guaranteed to be non-empty, ASCII and
+/// zero terminated so that it may be passed straight to the DLL.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImageName {
+    name: CString,
+}
+
+impl ImageName {
+    /// Returns an error if `name` is empty or contains a non-ASCII or NUL character.
+    pub fn new<S>(name: S) -> Result<Self>
+        where S: Into<String>,
+    {
+        let name = name.into();
+        if name.is_empty() || !name.bytes().all(|b| b.is_ascii() && b != 0) {
+            return Err(Error::InvalidImageName(name));
+        }
+        let name = CString::new(name).expect("name contains no NUL bytes");
+        Ok(ImageName { name })
+    }
+
+    /// The name without its terminating 0.
+    pub fn as_str(&self) -> &str {
+        self.name.to_str().expect("name is ASCII")
+    }
+
+    /// A pointer to the zero terminated name, valid for as long as `self` is.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.name.as_ptr() as *const u8
+    }
+}
+
+impl fmt::Display for ImageName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether an SDK image lives within a projection zone or a projector.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ImageKind {
+    Zone,
+    Projector,
+}
+
+/// An SDK image created within Beyond via `create_zone_image` or `create_projector_image`.
+///
+/// The image is deleted from Beyond's image-list when the `Image` is dropped.
+///
+/// Beyond's zone count is queried once when the image is created. Frames sent via the image are
+/// checked against it, so that zones Beyond does not have are rejected without an extra call per
+/// frame.
+pub struct Image<'a, B: 'a + ?Sized + LaserBackend> {
+    backend: &'a B,
+    kind: ImageKind,
+    index: i32,
+    name: ImageName,
+    zone_count: i32,
+}
+
+impl<'a, B: ?Sized + LaserBackend> Image<'a, B> {
+    pub(crate) fn new(backend: &'a B,
+                      kind: ImageKind,
+                      index: i32,
+                      name: ImageName,
+                      zone_count: i32) -> Self
+    {
+        Image { backend, kind, index, name, zone_count }
+    }
+
+    pub fn kind(&self) -> ImageKind {
+        self.kind
+    }
+
+    /// The index of the zone or projector that the image was created within.
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+
+    pub fn name(&self) -> &ImageName {
+        &self.name
+    }
+
+    /// The number of zones in Beyond when the image was created.
+    pub fn zone_count(&self) -> i32 {
+        self.zone_count
+    }
+
+    /// Send a frame to this image. See `LaserBackend::send_frame_to_image`.
+    ///
+    /// Returns an error if any of the zones is not less than `zone_count`.
+    pub fn send_frame(&self,
+                      laser_points: &[BeyondLaserPoint],
+                      zones: &ZoneSet,
+                      scan_rate: ScanRate) -> Result<()>
+    {
+        zones.check_zone_count(self.zone_count)?;
+        self.backend.send_frame_to_image(&self.name, laser_points, zones, scan_rate)
+    }
+
+    /// Send a frame to this image. See `LaserBackend::send_frame_to_image_with`.
+    ///
+    /// Returns an error if any of the zones is not less than `zone_count`.
+    pub fn send_frame_with(&self,
+                           reducer: &dyn FrameReducer,
+                           laser_points: &[BeyondLaserPoint],
+                           zones: &ZoneSet,
+                           scan_rate: ScanRate) -> Result<()>
+    {
+        zones.check_zone_count(self.zone_count)?;
+        self.backend.send_frame_to_image_with(reducer, &self.name, laser_points, zones, scan_rate)
+    }
+}
+
+impl<'a, B: ?Sized + LaserBackend> Drop for Image<'a, B> {
+    fn drop(&mut self) {
+        // Beyond may have already gone away, in which case there's nothing left to delete.
+        let _ = match self.kind {
+            ImageKind::Zone => self.backend.delete_zone_image(&self.name),
+            ImageKind::Projector => self.backend.delete_projector_image(&self.name),
+        };
+    }
+}
+
+#[test]
+fn test_image_name() {
+    let name = ImageName::new("/output1").unwrap();
+    assert_eq!(name.as_str(), "/output1");
+    assert_eq!(name.name.as_bytes_with_nul(), b"/output1\0");
+    assert!(ImageName::new(String::new()).is_err());
+    assert!(ImageName::new("/out\0put").is_err());
+    assert!(ImageName::new("/ausgabe\u{e4}").is_err());
+}
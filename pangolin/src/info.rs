@@ -0,0 +1,109 @@
+use {Error, LaserBackend, Result};
+use std::fmt;
+
+/// The oldest BEYONDIO.dll version supported, as returned by `get_dll_version`.
+pub const MIN_DLL_VERSION: i32 = 100;
+
+/// The oldest Beyond build supported, as returned by `get_beyond_version`.
+pub const MIN_BEYOND_VERSION: i32 = 712;
+
+/// The versions and state of BEYONDIO.dll and the Beyond instance behind it.
+///
+/// The build number and counts are zero while Beyond is not started or not ready, so a snapshot
+/// taken before then says little about what will be available once it is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BeyondInfo {
+    pub exe_started: bool,
+    pub exe_ready: bool,
+    pub beyond_version: i32,
+    pub dll_version: i32,
+    pub projector_count: i32,
+    pub zone_count: i32,
+}
+
+impl BeyondInfo {
+    /// Query each value from the given backend.
+    pub fn query<B: ?Sized + LaserBackend>(backend: &B) -> Result<Self> {
+        Ok(BeyondInfo {
+            exe_started: backend.beyond_exe_started()?,
+            exe_ready: backend.beyond_exe_ready()?,
+            beyond_version: backend.get_beyond_version()?,
+            dll_version: backend.get_dll_version()?,
+            projector_count: backend.get_projector_count()?,
+            zone_count: backend.get_zone_count()?,
+        })
+    }
+
+    /// Returns an error if the DLL, or Beyond once started, is older than the minimum supported.
+    pub fn check_versions(&self) -> Result<()> {
+        if self.dll_version < MIN_DLL_VERSION {
+            return Err(Error::UnsupportedVersion {
+                component: "BEYONDIO.dll",
+                version: self.dll_version,
+                minimum: MIN_DLL_VERSION,
+            });
+        }
+        if self.exe_started && self.beyond_version < MIN_BEYOND_VERSION {
+            return Err(Error::UnsupportedVersion {
+                component: "Beyond",
+                version: self.beyond_version,
+                minimum: MIN_BEYOND_VERSION,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns an error if Beyond has fewer projectors or zones than required.
+    pub fn check_outputs(&self, projectors_required: i32, zones_required: i32) -> Result<()> {
+        if self.projector_count < projectors_required {
+            return Err(Error::InsufficientOutputs {
+                kind: "projectors",
+                required: projectors_required,
+                available: self.projector_count,
+            });
+        }
+        if self.zone_count < zones_required {
+            return Err(Error::InsufficientOutputs {
+                kind: "zones",
+                required: zones_required,
+                available: self.zone_count,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BeyondInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Beyond build {} (started: {}, ready: {}), BEYONDIO.dll version {}, \
+                   {} projectors, {} zones",
+               self.beyond_version, self.exe_started, self.exe_ready, self.dll_version,
+               self.projector_count, self.zone_count)
+    }
+}
+
+#[test]
+fn test_beyond_info() {
+    use SimulatedBeyond;
+
+    let beyond = SimulatedBeyond::new(2, 4);
+    let info = beyond.beyond_info().unwrap();
+    assert_eq!(info, BeyondInfo {
+        exe_started: true,
+        exe_ready: true,
+        beyond_version: 712,
+        dll_version: 100,
+        projector_count: 2,
+        zone_count: 4,
+    });
+    info.check_versions().unwrap();
+    info.check_outputs(2, 4).unwrap();
+    match info.check_outputs(1, 5) {
+        Err(Error::InsufficientOutputs { kind: "zones", required: 5, available: 4 }) => (),
+        result => panic!("expected too few zones, found {:?}", result),
+    }
+    let old = BeyondInfo { beyond_version: 600, ..info };
+    assert!(old.check_versions().is_err());
+    let stopped = BeyondInfo { exe_started: false, beyond_version: 0, ..info };
+    stopped.check_versions().unwrap();
+}
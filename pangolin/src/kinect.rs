@@ -0,0 +1,75 @@
+use {supported, Error, Pangolin, Result};
+use std::os::raw::c_void;
+
+/// The number of skeletons Beyond accepts via `set_skeleton`.
+pub const SKELETON_COUNT: i32 = 2;
+
+/// The number of nodes within a single skeleton.
+pub const SKELETON_NODE_COUNT: usize = 20;
+
+/// A single skeleton node, laid out exactly as the SDK's packed `TKPoint` record.
+///
+/// Coordinates are in meters, where 1.0 is one meter.
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SkeletonNode {
+    x: f32,
+    y: f32,
+    z: f32,
+    /// 1 if the node is active, otherwise 0.
+    active: u8,
+}
+
+/// A full set of skeleton nodes, as would be delivered to Beyond by a Kinect controller.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Skeleton(pub [SkeletonNode; SKELETON_NODE_COUNT]);
+
+impl SkeletonNode {
+    pub fn new(x: f32, y: f32, z: f32, active: bool) -> Self {
+        SkeletonNode { x, y, z, active: active as u8 }
+    }
+
+    /// An inactive node at the origin.
+    pub fn inactive() -> Self {
+        SkeletonNode::default()
+    }
+
+    /// The `[x, y, z]` coordinates of the node in meters.
+    pub fn position(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active != 0
+    }
+}
+
+impl Pangolin {
+    /// Supply skeleton data to Beyond, as though it came from a Kinect controller.
+    ///
+    /// `index` must be less than `SKELETON_COUNT`. The SDK doesn't document the value returned by
+    /// `ldbSetKinect`, so it is ignored.
+    pub fn set_skeleton(&self, index: i32, skeleton: &Skeleton) -> Result<()> {
+        if !(0..SKELETON_COUNT).contains(&index) {
+            return Err(Error::InvalidSkeletonIndex(index));
+        }
+        let data = skeleton.0.as_ptr() as *const c_void;
+        let function = supported(self.ldb_set_kinect, "ldbSetKinect")?;
+        unsafe {
+            function(index, data);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_skeleton_layout() {
+    use std::mem;
+    assert_eq!(mem::size_of::<SkeletonNode>(), 13);
+    assert_eq!(mem::size_of::<Skeleton>(), 13 * SKELETON_NODE_COUNT);
+    let node = SkeletonNode::new(1.0, 2.0, 3.0, true);
+    assert_eq!(node.position(), [1.0, 2.0, 3.0]);
+    assert!(node.is_active());
+    assert!(!SkeletonNode::inactive().is_active());
+}
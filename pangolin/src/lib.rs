@@ -2,25 +2,110 @@
 
 extern crate find_folder;
 extern crate libloading;
+pub use backend::LaserBackend;
+pub use capabilities::Capabilities;
+pub use channels::{Channels, MAX_CHANNELS};
+pub use dmx::{DmxInput, DmxUniverse, DMX_UNIVERSE_CHANNELS};
+pub use error::{Error, Result};
+pub use image::{Image, ImageKind, ImageName};
+pub use info::{BeyondInfo, MIN_BEYOND_VERSION, MIN_DLL_VERSION};
+pub use kinect::{Skeleton, SkeletonNode, SKELETON_COUNT, SKELETON_NODE_COUNT};
 pub use libloading::{Library, Symbol};
+pub use locator::{LibraryLocator, LocatorAttempt, DEFAULT_ENV_VAR, DLL_NAME};
+pub use midi::{MidiDevicePair, MidiMessage};
+pub use reduce::{reduce_frame, DropBlankedTravel, FrameReducer, ReducerChain, RemoveCollinear,
+                 UniformResample};
+pub use scan_rate::ScanRate;
+pub use simulated::{SimulatedBeyond, SimulatedFrame};
+pub use submitter::{FrameSubmitter, SubmitReport};
+pub use supervisor::{ConnectionEvent, ConnectionState, Supervisor};
+pub use tick::{ProjectorTick, TimerTick};
+#[cfg(windows)]
+pub use tick::ProjectorEvent;
+pub use timecode::Timecode;
+pub use trace::{replay, ReplayStats, TraceCall, TraceReader, TraceRecord, TraceRecorder,
+                TraceResult};
+pub use timeline::Timeline;
+pub use zone::{ZoneArray, ZoneSet, MAX_ZONES};
 use std::os::raw::c_void;
+use std::{mem, ptr};
 
-pub struct Pangolin<'lib> {
-    ldb_destroy: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_beyond_exe_started: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_beyond_exe_ready: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_enable_laser_output: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_disable_laser_output: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_blackout: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_get_dll_version: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_get_beyond_version: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_get_projector_count: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_get_zone_count: Symbol<'lib, unsafe extern fn() -> i32>,
-    ldb_create_zone_image: Symbol<'lib, unsafe extern fn(i32, *const u8) -> i32>,
-    ldb_create_projector_image: Symbol<'lib, unsafe extern fn(i32, *const u8) -> i32>,
-    ldb_delete_zone_image: Symbol<'lib, unsafe extern fn(*const u8) -> i32>,
-    ldb_delete_projector_image: Symbol<'lib, unsafe extern fn(*const u8) -> i32>,
-    ldb_send_frame_to_image: Symbol<'lib, unsafe extern fn(*const u8, i32, *const c_void, *const c_void, i32 ) -> i32>,
+mod backend;
+mod capabilities;
+mod channels;
+mod dmx;
+mod error;
+mod image;
+mod info;
+mod kinect;
+mod locator;
+mod midi;
+mod reduce;
+mod scan_rate;
+mod simulated;
+mod submitter;
+mod supervisor;
+mod timecode;
+mod tick;
+mod timeline;
+mod trace;
+mod zone;
+
+/// The maximum number of points Beyond can buffer for a single frame.
+pub const MAX_NUM_POINTS: usize = 8192;
+
+/// An owned handle to BEYONDIO.dll.
+///
+/// The handle holds the loaded `Library` alongside the functions resolved from it, so it can be
+/// moved between threads and stored along with other state.
+///
+/// **Thread Safety**
+///
+/// `Pangolin` is both `Send` and `Sync` and may be shared between threads behind an `Arc`. The SDK
+/// only documents a critical section for `ldbSendFrameToImage`, which guards the DLL's global
+/// transfer buffer for the whole call. The other functions rely on Beyond's window handling the
+/// `SendMessage` from each call one at a time; concurrent calls are not otherwise serialised.
+///
+/// Functions added to the SDK after the initial zone image API are optional. Calling one that is
+/// not exported by the loaded DLL returns `Error::Unsupported`. See `Pangolin::capabilities`.
+///
+/// `ldbCreate` and `ldbDestroy` set up and release process-wide DLL resources, so only one
+/// `Pangolin` should exist per process.
+pub struct Pangolin {
+    ldb_destroy: unsafe extern "C" fn() -> i32,
+    ldb_beyond_exe_started: unsafe extern "C" fn() -> i32,
+    ldb_beyond_exe_ready: unsafe extern "C" fn() -> i32,
+    ldb_enable_laser_output: unsafe extern "C" fn() -> i32,
+    ldb_disable_laser_output: unsafe extern "C" fn() -> i32,
+    ldb_blackout: unsafe extern "C" fn() -> i32,
+    ldb_get_dll_version: unsafe extern "C" fn() -> i32,
+    ldb_get_beyond_version: unsafe extern "C" fn() -> i32,
+    ldb_get_projector_count: unsafe extern "C" fn() -> i32,
+    ldb_get_zone_count: unsafe extern "C" fn() -> i32,
+    ldb_create_zone_image: unsafe extern "C" fn(i32, *const u8) -> i32,
+    ldb_create_projector_image: Option<unsafe extern "C" fn(i32, *const u8) -> i32>,
+    ldb_delete_zone_image: unsafe extern "C" fn(*const u8) -> i32,
+    ldb_delete_projector_image: Option<unsafe extern "C" fn(*const u8) -> i32>,
+    ldb_send_frame_to_image: unsafe extern "C" fn(*const u8, i32, *const c_void, *const c_void, i32 ) -> i32,
+    ldb_get_time_code: Option<unsafe extern "C" fn() -> i32>,
+    ldb_set_time_code: Option<unsafe extern "C" fn(i32) -> i32>,
+    ldb_set_midi_in: Option<unsafe extern "C" fn(u8, u8, u8, u8) -> i32>,
+    ldb_set_midi_out: Option<unsafe extern "C" fn(u8, u8, u8, u8) -> i32>,
+    ldb_set_dmx: Option<unsafe extern "C" fn(i32, *const c_void) -> i32>,
+    ldb_set_channels: Option<unsafe extern "C" fn(*const c_void, i32) -> i32>,
+    ldb_timeline_play: Option<unsafe extern "C" fn() -> i32>,
+    ldb_timeline_stop: Option<unsafe extern "C" fn() -> i32>,
+    ldb_timeline_get_playing: Option<unsafe extern "C" fn() -> i32>,
+    ldb_timeline_set_pos: Option<unsafe extern "C" fn(i32) -> i32>,
+    ldb_timeline_get_pos: Option<unsafe extern "C" fn() -> i32>,
+    ldb_timeline_get_duration: Option<unsafe extern "C" fn() -> i32>,
+    ldb_timeline_set_online: Option<unsafe extern "C" fn(i32) -> i32>,
+    ldb_timeline_get_online: Option<unsafe extern "C" fn() -> i32>,
+    ldb_set_kinect: Option<unsafe extern "C" fn(i32, *const c_void) -> i32>,
+    #[cfg(windows)]
+    ldb_get_projector_event: Option<unsafe extern "C" fn(i32) -> *mut c_void>,
+    // Keeps the functions above valid for the lifetime of the handle.
+    _lib: Library,
 }
 
 /*
@@ -36,7 +121,7 @@ You need have array with points and supply pointed on this array into ldSendFram
 */
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct BeyondLaserPoint {
     /// 32bit float point, Coordinate system -32k to +32k
     x: f32, 
@@ -54,220 +139,358 @@ pub struct BeyondLaserPoint {
     zero: u8,
 }
 
-pub fn load_library() -> libloading::Result<Library> {
-    let exe_path = std::env::current_exe()?;
-    let pangolin_path = find_folder::Search::ParentsThenKids(7, 7)
-        .of(exe_path.parent().unwrap().into())
-        .for_folder("pangolin")
-        .unwrap();
-    let path = pangolin_path.join("BEYONDIO.dll");
-    let lib = Library::new(path)?;
-    Ok(lib)
+/// Load BEYONDIO.dll from the first location found by the default `LibraryLocator`.
+pub fn load_library() -> Result<Library> {
+    LibraryLocator::new().load()
 }
 
-impl<'lib> Pangolin<'lib> {
-    pub fn new(lib: &'lib Library) -> libloading::Result<Self> {
+/// Resolve a function that may not be exported by older builds of BEYONDIO.dll.
+unsafe fn optional<T: Copy>(lib: &Library, symbol: &[u8]) -> Option<T> {
+    lib.get::<T>(symbol).ok().map(|function| *function)
+}
 
-        unsafe {
-            let create: Symbol<unsafe extern fn() -> i32> = lib.get(b"ldbCreate\0")?;
-            //Return type of 1 equals success
-            assert!(create()==1);
+/// The given optional function, or `Error::Unsupported` if the loaded DLL does not export it.
+fn supported<T>(function: Option<T>, name: &'static str) -> Result<T> {
+    function.ok_or(Error::Unsupported(name))
+}
 
-            let pangolin = Pangolin {
-                ldb_destroy: lib.get(b"ldbDestroy\0")?,
-                ldb_beyond_exe_started: lib.get(b"ldbBeyondExeStarted\0")?,
-                ldb_beyond_exe_ready: lib.get(b"ldbBeyondExeReady\0")?,
-                ldb_enable_laser_output: lib.get(b"ldbEnableLaserOutput\0")?,
-                ldb_disable_laser_output: lib.get(b"ldbDisableLaserOutput\0")?,
-                ldb_blackout: lib.get(b"ldbBlackout\0")?,
-                ldb_get_dll_version: lib.get(b"ldbGetDllVersion\0")?,
-                ldb_get_beyond_version: lib.get(b"ldbGetBeyondVersion\0")?,
-                ldb_get_projector_count: lib.get(b"ldbGetProjectorCount\0")?,
-                ldb_get_zone_count: lib.get(b"ldbGetZoneCount\0")?,
-                ldb_create_zone_image: lib.get(b"ldbCreateZoneImage\0")?,
-                ldb_create_projector_image: lib.get(b"ldbCreateProjectorImage\0")?,
-                ldb_delete_zone_image: lib.get(b"ldbDeleteZoneImage\0")?,
-                ldb_delete_projector_image: lib.get(b"ldbDeleteProjectorImage\0")?,
-                ldb_send_frame_to_image: lib.get(b"ldbSendFrameToImage\0")?,
-            };
-            Ok(pangolin)
-        }
+impl Pangolin {
+    /// Load BEYONDIO.dll via `load_library` and initialise it.
+    pub fn load() -> Result<Self> {
+        Pangolin::new(load_library()?)
     }
 
-    /// Positive value indicates a percentage of the projector scan rate.
-    /// Negative value indicates *actual* scan rate (i.e. -30000 is 30000hz).
-    ///Image name is a zero terminated ANSII string 
-    pub fn send_frame_to_image(&self, image_name: &[u8], mut laser_points: &[BeyondLaserPoint], zone_indices: &[u8], scan_rate: i32) -> i32 {
-        // Make sure we dont exceed the max num of points for Pangolin
-        const MAX_NUM_POINTS: usize = 8192;
-        if laser_points.len() > MAX_NUM_POINTS {
-            println!("WARNING: Pangolin::send_frame_to_image received too many points {}. Truncating to {}.",
-                     laser_points.len(), MAX_NUM_POINTS);
-            laser_points = &laser_points[..MAX_NUM_POINTS];
-        }
+    /// Load BEYONDIO.dll from the first location found by `locator` and initialise it.
+    pub fn load_with(locator: &LibraryLocator) -> Result<Self> {
+        Pangolin::new(locator.load()?)
+    }
+
+    /// Initialise the given BEYONDIO.dll, taking ownership of it.
+    ///
+    /// Every function is resolved before `ldbCreate` is called, so that a missing export can't
+    /// leave the DLL created without a `Pangolin` to destroy it.
+    pub fn new(lib: Library) -> Result<Self> {
 
-        let mut zone_array = [0u8; 256];
-        let mut i = 0;
-        assert!(zone_indices.len() < 255);
-        while i < zone_indices.len() {
-            zone_array[i] = 1 + zone_indices[i];
-            i+=1;
+        unsafe {
+            let create: unsafe extern "C" fn() -> i32 = *lib.get(b"ldbCreate\0")?;
+            let pangolin = Pangolin {
+                ldb_destroy: *lib.get(b"ldbDestroy\0")?,
+                ldb_beyond_exe_started: *lib.get(b"ldbBeyondExeStarted\0")?,
+                ldb_beyond_exe_ready: *lib.get(b"ldbBeyondExeReady\0")?,
+                ldb_enable_laser_output: *lib.get(b"ldbEnableLaserOutput\0")?,
+                ldb_disable_laser_output: *lib.get(b"ldbDisableLaserOutput\0")?,
+                ldb_blackout: *lib.get(b"ldbBlackout\0")?,
+                ldb_get_dll_version: *lib.get(b"ldbGetDllVersion\0")?,
+                ldb_get_beyond_version: *lib.get(b"ldbGetBeyondVersion\0")?,
+                ldb_get_projector_count: *lib.get(b"ldbGetProjectorCount\0")?,
+                ldb_get_zone_count: *lib.get(b"ldbGetZoneCount\0")?,
+                ldb_create_zone_image: *lib.get(b"ldbCreateZoneImage\0")?,
+                ldb_create_projector_image: optional(&lib, b"ldbCreateProjectorImage\0"),
+                ldb_delete_zone_image: *lib.get(b"ldbDeleteZoneImage\0")?,
+                ldb_delete_projector_image: optional(&lib, b"ldbDeleteProjectorImage\0"),
+                ldb_send_frame_to_image: *lib.get(b"ldbSendFrameToImage\0")?,
+                ldb_get_time_code: optional(&lib, b"ldbGetTimeCode\0"),
+                ldb_set_time_code: optional(&lib, b"ldbSetTimeCode\0"),
+                ldb_set_midi_in: optional(&lib, b"ldbSetMidiIn\0"),
+                ldb_set_midi_out: optional(&lib, b"ldbSetMidiOut\0"),
+                ldb_set_dmx: optional(&lib, b"ldbSetDmx\0"),
+                ldb_set_channels: optional(&lib, b"ldbSetChannels\0"),
+                ldb_timeline_play: optional(&lib, b"ldbTimelinePlay\0"),
+                ldb_timeline_stop: optional(&lib, b"ldbTimelineStop\0"),
+                ldb_timeline_get_playing: optional(&lib, b"ldbTimelineGetPlaying\0"),
+                ldb_timeline_set_pos: optional(&lib, b"ldbTimelineSetPos\0"),
+                ldb_timeline_get_pos: optional(&lib, b"ldbTimelineGetPos\0"),
+                ldb_timeline_get_duration: optional(&lib, b"ldbTimelineGetDuration\0"),
+                ldb_timeline_set_online: optional(&lib, b"ldbTimelineSetOnline\0"),
+                ldb_timeline_get_online: optional(&lib, b"ldbTimelineGetOnline\0"),
+                ldb_set_kinect: optional(&lib, b"ldbSetKinect\0"),
+                #[cfg(windows)]
+                ldb_get_projector_event: optional(&lib, b"ldbGetProjectorEvent\0"),
+                _lib: lib,
+            };
+
+            //Return type of 1 equals success
+            match create() {
+                1 => Ok(pangolin),
+                code => {
+                    // Release the library without running `Drop`, as there is nothing to destroy.
+                    // The remaining fields are plain function pointers.
+                    let lib = ptr::read(&pangolin._lib);
+                    mem::forget(pangolin);
+                    drop(lib);
+                    Err(Error::Create { code })
+                },
+            }
         }
-        // Last elem is indicated by a trailing 0
-        zone_array[i] = 0;
+    }
 
+    pub fn destroy(&self) -> Result<()> {
         unsafe {
-            (self.ldb_send_frame_to_image)(image_name.as_ptr(), 
-                                           laser_points.len() as i32, 
-                                           laser_points.as_ptr() as *const c_void, 
-                                           zone_array.as_ptr() as *const c_void, 
-                                           scan_rate) 
+            error::check("ldbDestroy", (self.ldb_destroy)())
         }
     }
+}
 
-    ///Image name is a zero terminated ANSII string 
-    pub fn create_zone_image(&self, zone_index: i32, image_name: &[u8]) -> i32 {
+impl LaserBackend for Pangolin {
+    fn beyond_exe_started(&self) -> Result<bool> {
         unsafe {
-            // No idea what i32 return is yet
-            (self.ldb_create_zone_image)(zone_index, image_name.as_ptr())
+            error::check_bool("ldbBeyondExeStarted", (self.ldb_beyond_exe_started)())
         }
     }
 
-    ///Image name is a zero terminated ANSII string 
-    pub fn create_projector_image(&self, projector_index: i32, image_name: &[u8]) -> i32 {
+    fn beyond_exe_ready(&self) -> Result<bool> {
         unsafe {
-            // No idea what i32 return is yet
-            (self.ldb_create_projector_image)(projector_index, image_name.as_ptr())
+            error::check_bool("ldbBeyondExeReady", (self.ldb_beyond_exe_ready)())
         }
     }
 
-    ///Image name is a zero terminated ANSII string 
-    pub fn delete_zone_image(&self, image_name: &[u8]) -> i32 {
+    fn enable_laser_output(&self) -> Result<()> {
         unsafe {
-            // No idea what i32 return is yet
-            (self.ldb_delete_zone_image)(image_name.as_ptr())
+            match (self.ldb_enable_laser_output)() {
+                // Not documented by the SDK, but returned when Beyond refuses to enable output.
+                -1 => Err(Error::Failed { function: "ldbEnableLaserOutput", code: -1 }),
+                code => error::check("ldbEnableLaserOutput", code),
+            }
         }
     }
 
-    ///Image name is a zero terminated ANSII string 
-    pub fn delete_projector_image(&self, image_name: &[u8]) -> i32 {
+    fn disable_laser_output(&self) -> Result<()> {
         unsafe {
-            // No idea what i32 return is yet
-            (self.ldb_delete_projector_image)(image_name.as_ptr())
+            error::check("ldbDisableLaserOutput", (self.ldb_disable_laser_output)())
         }
     }
 
-    pub fn get_dll_version(&self) -> i32 {
+    fn blackout(&self) -> Result<()> {
         unsafe {
-            (self.ldb_get_dll_version)()
+            error::check("ldbBlackout", (self.ldb_blackout)())
         }
     }
 
-    pub fn get_beyond_version(&self) -> i32 {
+    fn get_dll_version(&self) -> Result<i32> {
         unsafe {
-            (self.ldb_get_beyond_version)()
+            Ok((self.ldb_get_dll_version)())
         }
     }
 
-    pub fn get_projector_count(&self) -> i32 {
+    fn get_beyond_version(&self) -> Result<i32> {
         unsafe {
-            (self.ldb_get_projector_count)()
+            error::check_count("ldbGetBeyondVersion", (self.ldb_get_beyond_version)())
         }
     }
 
-    pub fn get_zone_count(&self) -> i32 {
+    fn get_projector_count(&self) -> Result<i32> {
         unsafe {
-            (self.ldb_get_zone_count)()
+            error::check_count("ldbGetProjectorCount", (self.ldb_get_projector_count)())
         }
     }
 
-    pub fn destroy(&self) -> i32 {
+    fn get_zone_count(&self) -> Result<i32> {
         unsafe {
-            (self.ldb_destroy)()
+            error::check_count("ldbGetZoneCount", (self.ldb_get_zone_count)())
         }
     }
 
-    pub fn beyond_exe_started(&self) -> bool {
+    fn send_frame(&self,
+                  image_name: &ImageName,
+                  laser_points: &[BeyondLaserPoint],
+                  zone_array: &ZoneArray,
+                  scan_rate: i32) -> Result<()>
+    {
         unsafe {
-            match (self.ldb_beyond_exe_started)() {
-                0 => false,
-                1 => true,
-                _ => unreachable!(),
-            }
+            let code = (self.ldb_send_frame_to_image)(image_name.as_ptr(),
+                                                      laser_points.len() as i32,
+                                                      laser_points.as_ptr() as *const c_void,
+                                                      zone_array.as_ptr() as *const c_void,
+                                                      scan_rate);
+            error::check("ldbSendFrameToImage", code)
         }
     }
 
-    pub fn beyond_exe_ready(&self) -> bool {
+    fn insert_zone_image(&self, zone_index: i32, image_name: &ImageName) -> Result<()> {
+        // The SDK doesn't document the return value, and Beyond ignores the call rather than
+        // failing for existing names and out-of-range zones, so the value isn't checked.
         unsafe {
-            match (self.ldb_beyond_exe_ready)() {
-                0 => false,
-                1 => true,
-                _ => unreachable!(),
-            }
+            (self.ldb_create_zone_image)(zone_index, image_name.as_ptr());
         }
+        Ok(())
     }
 
-    pub fn enable_laser_output(&self) -> bool {
+    fn insert_projector_image(&self, projector_index: i32, image_name: &ImageName) -> Result<()> {
+        let function = supported(self.ldb_create_projector_image, "ldbCreateProjectorImage")?;
+        // As with `ldbCreateZoneImage`, the return value is undocumented and isn't checked.
         unsafe {
-            match (self.ldb_enable_laser_output)() {
-                0 | -1 => false,
-                1 => true,
-                n => panic!("{:?}",n),
-            }
+            function(projector_index, image_name.as_ptr());
         }
+        Ok(())
     }
 
-    pub fn disable_laser_output(&self) -> bool {
+    fn delete_zone_image(&self, image_name: &ImageName) -> Result<()> {
         unsafe {
-            match (self.ldb_disable_laser_output)() {
-                0 => false,
-                1 => true,
-                _ => unreachable!(),
-            }
+            error::check("ldbDeleteZoneImage", (self.ldb_delete_zone_image)(image_name.as_ptr()))
         }
     }
 
-    pub fn blackout(&self) -> bool {
+    fn delete_projector_image(&self, image_name: &ImageName) -> Result<()> {
+        let function = supported(self.ldb_delete_projector_image, "ldbDeleteProjectorImage")?;
         unsafe {
-            match (self.ldb_blackout)() {
-                0 => false,
-                1 => true,
-                _ => unreachable!(),
-            }
+            let code = function(image_name.as_ptr());
+            error::check("ldbDeleteProjectorImage", code)
         }
     }
 }
 
-impl<'lib> Drop for Pangolin<'lib> {
+impl Drop for Pangolin {
     fn drop(&mut self){
-        self.disable_laser_output();
-        self.destroy();
+        // There's nothing useful to be done with errors while dropping.
+        let _ = self.disable_laser_output();
+        let _ = self.destroy();
     }
 }
 
 impl BeyondLaserPoint {
+    /// The largest coordinate used when converting from normalised coordinates.
+    pub const MAX_COORDINATE: f32 = 32_000.0;
+
     /// Coords are normalised
+    ///
+    /// `0.0` maps to `-MAX_COORDINATE` and `1.0` to `MAX_COORDINATE`, except for `y` which is
+    /// flipped so that `0.0` is at the top.
     pub fn new(x: f32, y: f32, z: f32, r: u8, g: u8, b: u8) -> Self {
-        let a = 0;
-        let colour = (a << 24i32) | ((b as i32) << 16i32) | ((g as i32) << 8i32) | (r as i32); 
+        BeyondLaserPoint::from_units(x * 64_000.0 - 32_000.0,
+                                     y * -64_000.0 + 32_000.0,
+                                     z * 64_000.0 - 32_000.0,
+                                     r, g, b)
+    }
+
+    /// Coords are in the range `-1.0` to `1.0`, where `1.0` maps to `MAX_COORDINATE`.
+    ///
+    /// Unlike `new`, the `y` axis is not flipped.
+    pub fn from_signed(x: f32, y: f32, z: f32, r: u8, g: u8, b: u8) -> Self {
+        let max = BeyondLaserPoint::MAX_COORDINATE;
+        BeyondLaserPoint::from_units(x * max, y * max, z * max, r, g, b)
+    }
+
+    /// Coords are in Beyond's own units, ranging from -32K to +32K.
+    pub fn from_units(x: f32, y: f32, z: f32, r: u8, g: u8, b: u8) -> Self {
+        BeyondLaserPoint::from_packed_colour(x, y, z, pack_colour(r, g, b))
+    }
+
+    /// Coords are in Beyond's own units and `colour` is packed as a Windows `COLORREF`, i.e.
+    /// `0x00BBGGRR`.
+    pub fn from_packed_colour(x: f32, y: f32, z: f32, colour: u32) -> Self {
         BeyondLaserPoint {
-            x: x * 64_000.0 - 32_000.0,
-            y: y * -64_000.0 + 32_000.0,
-            z: z * 64_000.0 - 32_000.0,
-            point_colour: colour,
+            x,
+            y,
+            z,
+            point_colour: (colour & 0x00FF_FFFF) as i32,
             rep_count: 0,
             focus: 0,
             status: 0,
             zero: 0,
         }
-    } 
+    }
+
+    /// The `[x, y, z]` coordinates in Beyond's units.
+    pub fn position(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// Set the `[x, y, z]` coordinates in Beyond's units.
+    pub fn set_position(&mut self, [x, y, z]: [f32; 3]) -> &mut Self {
+        self.x = x;
+        self.y = y;
+        self.z = z;
+        self
+    }
+
+    /// The `[r, g, b]` colour components.
+    pub fn rgb(&self) -> [u8; 3] {
+        let colour = self.point_colour;
+        [colour as u8, (colour >> 8) as u8, (colour >> 16) as u8]
+    }
+
+    pub fn set_rgb(&mut self, r: u8, g: u8, b: u8) -> &mut Self {
+        self.point_colour = pack_colour(r, g, b) as i32;
+        self
+    }
+
+    /// The colour packed as a Windows `COLORREF`, i.e. `0x00BBGGRR`.
+    pub fn packed_colour(&self) -> u32 {
+        self.point_colour as u32
+    }
+
+    /// Whether the point is black, i.e. the laser is off while moving to it.
+    pub fn is_blank(&self) -> bool {
+        self.packed_colour() == 0
+    }
+
+    /// The number of times the point is repeated. 0 means the point is drawn once.
+    pub fn rep_count(&self) -> u8 {
+        self.rep_count
+    }
+
+    pub fn set_rep_count(&mut self, rep_count: u8) -> &mut Self {
+        self.rep_count = rep_count;
+        self
+    }
+
+    /// Beam brush, currently unused by Beyond.
+    pub fn focus(&self) -> u8 {
+        self.focus
+    }
+
+    pub fn set_focus(&mut self, focus: u8) -> &mut Self {
+        self.focus = focus;
+        self
+    }
+
+    /// The attribute bitmask. The SDK asks that this is left as zero for now.
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: u8) -> &mut Self {
+        self.status = status;
+        self
+    }
 }
 
+/// Pack the given colour components as a Windows `COLORREF`, i.e. `0x00BBGGRR`.
+fn pack_colour(r: u8, g: u8, b: u8) -> u32 {
+    ((b as u32) << 16) | ((g as u32) << 8) | (r as u32)
+}
+
+// Requires BEYONDIO.dll and a running instance of Beyond.
+#[cfg(windows)]
 #[test]
 fn test() {
-    let lib = load_library().unwrap();
-    let pangolin = Pangolin::new(&lib).unwrap();
-    assert!(pangolin.beyond_exe_started());
-    assert!(pangolin.enable_laser_output());
-    println!("Dll version = {}", pangolin.get_dll_version());
+    let pangolin = Pangolin::load().unwrap();
+    assert!(pangolin.beyond_exe_started().unwrap());
+    pangolin.enable_laser_output().unwrap();
+    println!("Dll version = {}", pangolin.get_dll_version().unwrap());
+}
+
+#[test]
+fn test_laser_point() {
+    let point = BeyondLaserPoint::new(0.0, 0.0, 0.5, 1, 2, 3);
+    assert_eq!(point.position(), [-32_000.0, 32_000.0, 0.0]);
+    assert_eq!(point.rgb(), [1, 2, 3]);
+    assert_eq!(point.packed_colour(), 0x0003_0201);
+    assert_eq!(BeyondLaserPoint::from_signed(-1.0, 1.0, 0.0, 1, 2, 3),
+               BeyondLaserPoint::from_units(-32_000.0, 32_000.0, 0.0, 1, 2, 3));
+    assert_eq!(BeyondLaserPoint::from_packed_colour(0.0, 0.0, 0.0, 0xFF03_0201).rgb(), [1, 2, 3]);
+
+    let mut point = BeyondLaserPoint::from_units(0.0, 0.0, 0.0, 0, 0, 0);
+    assert!(point.is_blank());
+    point.set_rep_count(2).set_focus(3).set_status(4).set_rgb(255, 0, 0);
+    point.set_position([1.0, 2.0, 3.0]);
+    assert_eq!((point.rep_count(), point.focus(), point.status()), (2, 3, 4));
+    assert_eq!(point.rgb(), [255, 0, 0]);
+    assert_eq!(point.position(), [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn test_pangolin_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Pangolin>();
 }
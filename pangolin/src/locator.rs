@@ -0,0 +1,200 @@
+use {find_folder, Error, Library, Result};
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The file name of the Beyond SDK library.
+pub const DLL_NAME: &str = "BEYONDIO.dll";
+
+/// The environment variable consulted by default for the location of BEYONDIO.dll.
+pub const DEFAULT_ENV_VAR: &str = "BEYONDIO_DLL";
+
+/// Finds and loads BEYONDIO.dll.
+///
+/// The following locations are tried in order, and the first library that loads is returned:
+///
+/// 1. The explicit `path`, if one was given.
+/// 2. The path held by the `env_var` environment variable, if it is set.
+/// 3. Each Beyond install directory.
+/// 4. A folder named `pangolin` found within 7 parents or children of the executable's folder.
+///
+/// Paths may either refer to the library itself or to the directory containing it.
+#[derive(Clone, Debug)]
+pub struct LibraryLocator {
+    path: Option<PathBuf>,
+    env_var: Option<String>,
+    install_dirs: Vec<PathBuf>,
+    search_exe_folders: bool,
+}
+
+/// A location that was tried while searching for BEYONDIO.dll, and why it was rejected.
+#[derive(Clone, Debug)]
+pub struct LocatorAttempt {
+    /// Describes where the location came from, e.g. "explicit path".
+    pub source: String,
+    /// The path that was tried, if the location resolved to one.
+    pub path: Option<PathBuf>,
+    pub reason: String,
+}
+
+impl LibraryLocator {
+    /// A locator that checks `DEFAULT_ENV_VAR`, the default Beyond install directories and the
+    /// folders surrounding the executable.
+    pub fn new() -> Self {
+        LibraryLocator {
+            path: None,
+            env_var: Some(DEFAULT_ENV_VAR.to_string()),
+            install_dirs: default_install_dirs(),
+            search_exe_folders: true,
+        }
+    }
+
+    /// Try this path before any other location.
+    pub fn path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Consult the given environment variable instead of `DEFAULT_ENV_VAR`, or none at all.
+    pub fn env_var(mut self, env_var: Option<String>) -> Self {
+        self.env_var = env_var;
+        self
+    }
+
+    /// Replace the Beyond install directories to search.
+    pub fn install_dirs(mut self, install_dirs: Vec<PathBuf>) -> Self {
+        self.install_dirs = install_dirs;
+        self
+    }
+
+    /// Whether or not to search for a `pangolin` folder surrounding the executable.
+    pub fn search_exe_folders(mut self, search: bool) -> Self {
+        self.search_exe_folders = search;
+        self
+    }
+
+    /// Load the first library found, or describe every location that was tried.
+    pub fn load(&self) -> Result<Library> {
+        let mut attempts = vec![];
+
+        if let Some(ref path) = self.path {
+            if let Some(lib) = try_load("explicit path", path, &mut attempts) {
+                return Ok(lib);
+            }
+        }
+
+        if let Some(ref var) = self.env_var {
+            let source = format!("environment variable {}", var);
+            match env::var_os(var) {
+                Some(path) => {
+                    if let Some(lib) = try_load(&source, Path::new(&path), &mut attempts) {
+                        return Ok(lib);
+                    }
+                },
+                None => attempts.push(LocatorAttempt {
+                    source,
+                    path: None,
+                    reason: "not set".to_string(),
+                }),
+            }
+        }
+
+        for dir in &self.install_dirs {
+            if let Some(lib) = try_load("Beyond install directory", dir, &mut attempts) {
+                return Ok(lib);
+            }
+        }
+
+        if self.search_exe_folders {
+            let source = "pangolin folder near the executable";
+            match exe_pangolin_folder() {
+                Ok(dir) => {
+                    if let Some(lib) = try_load(source, &dir, &mut attempts) {
+                        return Ok(lib);
+                    }
+                },
+                Err(reason) => attempts.push(LocatorAttempt {
+                    source: source.to_string(),
+                    path: None,
+                    reason,
+                }),
+            }
+        }
+
+        Err(Error::LibraryNotFound(attempts))
+    }
+}
+
+impl Default for LibraryLocator {
+    fn default() -> Self {
+        LibraryLocator::new()
+    }
+}
+
+impl fmt::Display for LocatorAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.path {
+            Some(ref path) => write!(f, "{} ({}): {}", self.source, path.display(), self.reason),
+            None => write!(f, "{}: {}", self.source, self.reason),
+        }
+    }
+}
+
+/// The directories that Beyond is installed to by default.
+fn default_install_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    for var in &["ProgramFiles", "ProgramFiles(x86)"] {
+        if let Some(program_files) = env::var_os(var) {
+            dirs.push(Path::new(&program_files).join("Pangolin").join("BEYOND"));
+        }
+    }
+    if cfg!(windows) {
+        dirs.push(PathBuf::from(r"C:\Pangolin\BEYOND"));
+    }
+    dirs
+}
+
+/// The original search: a `pangolin` folder within 7 parents or children of the executable.
+fn exe_pangolin_folder() -> ::std::result::Result<PathBuf, String> {
+    let exe_path = env::current_exe().map_err(|err| err.to_string())?;
+    let exe_dir = exe_path.parent()
+        .ok_or_else(|| "the executable has no parent directory".to_string())?;
+    find_folder::Search::ParentsThenKids(7, 7)
+        .of(exe_dir.into())
+        .for_folder("pangolin")
+        .map_err(|err| format!("{:?}", err))
+}
+
+/// Load the library at `path`, or within `path` if it is a directory, recording any failure.
+fn try_load(source: &str, path: &Path, attempts: &mut Vec<LocatorAttempt>) -> Option<Library> {
+    let path = if path.is_dir() { path.join(DLL_NAME) } else { path.to_path_buf() };
+    let result = if path.is_file() {
+        Library::new(&path).map_err(|err| err.to_string())
+    } else {
+        Err("no such file".to_string())
+    };
+    match result {
+        Ok(lib) => Some(lib),
+        Err(reason) => {
+            attempts.push(LocatorAttempt { source: source.to_string(), path: Some(path), reason });
+            None
+        },
+    }
+}
+
+#[test]
+fn test_locator_reports_attempts() {
+    let locator = LibraryLocator::new()
+        .path("/nonexistent/BEYONDIO.dll")
+        .env_var(Some("PANGOLIN_TEST_UNSET_VARIABLE".to_string()))
+        .install_dirs(vec![PathBuf::from("/nonexistent/Pangolin/BEYOND")])
+        .search_exe_folders(false);
+    let attempts = match locator.load() {
+        Err(Error::LibraryNotFound(attempts)) => attempts,
+        _ => panic!("expected LibraryNotFound"),
+    };
+    assert_eq!(attempts.len(), 3);
+    assert_eq!(attempts[0].path, Some(PathBuf::from("/nonexistent/BEYONDIO.dll")));
+    assert_eq!(attempts[1].reason, "not set");
+    assert_eq!(attempts[2].path, Some(PathBuf::from("/nonexistent/Pangolin/BEYOND")));
+}
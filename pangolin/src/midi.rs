@@ -0,0 +1,133 @@
+use {supported, Error, Pangolin, Result};
+
+/// One of Beyond's four MIDI IN/OUT device pairs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MidiDevicePair {
+    index: u8,
+}
+
+/// A single MIDI message, consisting of a command (status) byte and two data bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MidiMessage {
+    command: u8,
+    data1: u8,
+    data2: u8,
+}
+
+impl MidiDevicePair {
+    /// The number of MIDI device pairs supported by Beyond.
+    pub const COUNT: u8 = 4;
+
+    /// Returns an error if `index` is not in the range `0..MidiDevicePair::COUNT`.
+    pub fn new(index: u8) -> Result<Self> {
+        if index >= MidiDevicePair::COUNT {
+            return Err(Error::InvalidMidiDevicePair(index));
+        }
+        Ok(MidiDevicePair { index })
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+}
+
+impl MidiMessage {
+    /// Returns an error if `command` is not a status byte (`0x80` and above) or if either data
+    /// byte is not in the range `0..0x80`.
+    pub fn new(command: u8, data1: u8, data2: u8) -> Result<Self> {
+        if command < 0x80 {
+            return Err(Error::InvalidMidiCommand(command));
+        }
+        if let Some(&data) = [data1, data2].iter().find(|&&data| data >= 0x80) {
+            return Err(Error::InvalidMidiData(data));
+        }
+        Ok(MidiMessage { command, data1, data2 })
+    }
+
+    /// Returns an error if `channel` is not in the range `0..16`.
+    fn channel_message(status: u8, channel: u8, data1: u8, data2: u8) -> Result<Self> {
+        if channel >= 16 {
+            return Err(Error::InvalidMidiChannel(channel));
+        }
+        MidiMessage::new(status | channel, data1, data2)
+    }
+
+    pub fn note_off(channel: u8, note: u8, velocity: u8) -> Result<Self> {
+        MidiMessage::channel_message(0x80, channel, note, velocity)
+    }
+
+    pub fn note_on(channel: u8, note: u8, velocity: u8) -> Result<Self> {
+        MidiMessage::channel_message(0x90, channel, note, velocity)
+    }
+
+    pub fn control_change(channel: u8, controller: u8, value: u8) -> Result<Self> {
+        MidiMessage::channel_message(0xB0, channel, controller, value)
+    }
+
+    pub fn program_change(channel: u8, program: u8) -> Result<Self> {
+        MidiMessage::channel_message(0xC0, channel, program, 0)
+    }
+
+    pub fn command(&self) -> u8 {
+        self.command
+    }
+
+    pub fn data1(&self) -> u8 {
+        self.data1
+    }
+
+    pub fn data2(&self) -> u8 {
+        self.data2
+    }
+}
+
+// The SDK doesn't document the values returned by `ldbSetMidiIn` and `ldbSetMidiOut`, so they are
+// ignored and the calls only fail if the function is unsupported.
+impl Pangolin {
+    /// Deliver a MIDI message to Beyond as though it came from the MIDI IN device of the given
+    /// pair.
+    pub fn set_midi_in(&self, message: MidiMessage, device: MidiDevicePair) -> Result<()> {
+        let MidiMessage { command, data1, data2 } = message;
+        let function = supported(self.ldb_set_midi_in, "ldbSetMidiIn")?;
+        unsafe {
+            function(command, data1, data2, device.index);
+        }
+        Ok(())
+    }
+
+    /// Ask Beyond to immediately send a MIDI message from the MIDI OUT device of the given pair.
+    ///
+    /// Useful for sending to a MIDI device that is already in use by Beyond.
+    pub fn set_midi_out(&self, message: MidiMessage, device: MidiDevicePair) -> Result<()> {
+        let MidiMessage { command, data1, data2 } = message;
+        let function = supported(self.ldb_set_midi_out, "ldbSetMidiOut")?;
+        unsafe {
+            function(command, data1, data2, device.index);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_midi_validation() {
+    assert!(MidiDevicePair::new(3).is_ok());
+    match MidiDevicePair::new(4) {
+        Err(Error::InvalidMidiDevicePair(4)) => (),
+        result => panic!("expected an invalid device pair, found {:?}", result),
+    }
+    match MidiMessage::new(0x7F, 0, 0) {
+        Err(Error::InvalidMidiCommand(0x7F)) => (),
+        result => panic!("expected an invalid command, found {:?}", result),
+    }
+    match MidiMessage::new(0x90, 0, 0x80) {
+        Err(Error::InvalidMidiData(0x80)) => (),
+        result => panic!("expected invalid data, found {:?}", result),
+    }
+    let note_on = MidiMessage::note_on(15, 60, 127).unwrap();
+    assert_eq!((note_on.command(), note_on.data1(), note_on.data2()), (0x9F, 60, 127));
+    match MidiMessage::note_on(16, 60, 127) {
+        Err(Error::InvalidMidiChannel(16)) => (),
+        result => panic!("expected an invalid channel, found {:?}", result),
+    }
+    assert_eq!(MidiMessage::program_change(0, 5).unwrap().data2(), 0);
+}
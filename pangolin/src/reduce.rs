@@ -0,0 +1,246 @@
+use BeyondLaserPoint;
+use std::borrow::Cow;
+
+/// A strategy for reducing the number of points in a frame that exceeds `MAX_NUM_POINTS`.
+///
+/// Reducers may return more than `max_points` if they find nothing more to remove.
+/// `reduce_frame` then resamples the remainder uniformly, so the result always fits.
+pub trait FrameReducer {
+    /// Remove points from a frame that has more than `max_points`.
+    fn reduce(&self, points: &[BeyondLaserPoint], max_points: usize) -> Vec<BeyondLaserPoint>;
+}
+
+/// Keeps evenly spaced points, always keeping the points on either side of a change between blank
+/// and lit so that the beam is never left on between shapes.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct UniformResample;
+
+/// Removes points that lie on the straight line between their neighbours and share the colour of
+/// the following segment.
+///
+/// Points with a repeat count are kept, as they are usually deliberate dwell points at corners.
+#[derive(Copy, Clone, Debug)]
+pub struct RemoveCollinear {
+    /// How far a point may lie from the line between its neighbours in Beyond's units.
+    pub tolerance: f32,
+}
+
+/// Removes blank points in the middle of a run of blank points, keeping the first and last of
+/// each run so that the beam still travels between the same places while off.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DropBlankedTravel;
+
+/// Applies each reducer in turn until the frame fits.
+pub struct ReducerChain {
+    reducers: Vec<Box<dyn FrameReducer + Send + Sync>>,
+}
+
+/// Reduce `points` to at most `max_points` with the given reducer, falling back to
+/// `UniformResample` if the reducer leaves too many.
+///
+/// Frames that already fit are returned unchanged.
+pub fn reduce_frame<'a>(reducer: &dyn FrameReducer,
+                        points: &'a [BeyondLaserPoint],
+                        max_points: usize) -> Cow<'a, [BeyondLaserPoint]>
+{
+    if points.len() <= max_points {
+        return Cow::Borrowed(points);
+    }
+    let reduced = reducer.reduce(points, max_points);
+    if reduced.len() <= max_points {
+        return Cow::Owned(reduced);
+    }
+    Cow::Owned(UniformResample.reduce(&reduced, max_points))
+}
+
+impl FrameReducer for UniformResample {
+    fn reduce(&self, points: &[BeyondLaserPoint], max_points: usize) -> Vec<BeyondLaserPoint> {
+        if points.len() <= max_points {
+            return points.to_vec();
+        }
+        let len = points.len();
+        let is_anchor = |i: usize| {
+            i == 0 || i == len - 1
+                || points[i].is_blank() != points[i - 1].is_blank()
+                || points[i].is_blank() != points[i + 1].is_blank()
+        };
+        let anchors = (0..len).filter(|&i| is_anchor(i)).count();
+        if anchors > max_points {
+            // Too many shapes to keep every transition, so sample without regard to blanking.
+            return resample(points, max_points);
+        }
+
+        // Share the remaining budget evenly between the points that are not anchors.
+        let others = len - anchors;
+        let budget = max_points - anchors;
+        let mut reduced = Vec::with_capacity(max_points);
+        let mut seen = 0;
+        let mut kept = 0;
+        for (i, &point) in points.iter().enumerate() {
+            if is_anchor(i) {
+                reduced.push(point);
+                continue;
+            }
+            seen += 1;
+            if kept < seen * budget / others {
+                reduced.push(point);
+                kept += 1;
+            }
+        }
+        reduced
+    }
+}
+
+impl RemoveCollinear {
+    pub fn new(tolerance: f32) -> Self {
+        RemoveCollinear { tolerance }
+    }
+}
+
+impl Default for RemoveCollinear {
+    fn default() -> Self {
+        RemoveCollinear::new(1.0)
+    }
+}
+
+impl FrameReducer for RemoveCollinear {
+    fn reduce(&self, points: &[BeyondLaserPoint], _max_points: usize) -> Vec<BeyondLaserPoint> {
+        let mut reduced: Vec<BeyondLaserPoint> = Vec::with_capacity(points.len());
+        for (i, &point) in points.iter().enumerate() {
+            let next = match points.get(i + 1) {
+                Some(next) => next,
+                None => {
+                    reduced.push(point);
+                    break;
+                },
+            };
+            let redundant = match reduced.last() {
+                Some(prev) => point.rep_count() == 0
+                    && point.packed_colour() == next.packed_colour()
+                    && distance_to_segment(&point, prev, next) <= self.tolerance,
+                None => false,
+            };
+            if !redundant {
+                reduced.push(point);
+            }
+        }
+        reduced
+    }
+}
+
+impl FrameReducer for DropBlankedTravel {
+    fn reduce(&self, points: &[BeyondLaserPoint], _max_points: usize) -> Vec<BeyondLaserPoint> {
+        points.iter()
+            .enumerate()
+            .filter(|&(i, point)| {
+                let between_blanks = i > 0 && i + 1 < points.len()
+                    && points[i - 1].is_blank()
+                    && points[i + 1].is_blank();
+                !(point.is_blank() && between_blanks)
+            })
+            .map(|(_, &point)| point)
+            .collect()
+    }
+}
+
+impl ReducerChain {
+    /// A chain that applies no reducers, leaving everything to `UniformResample`.
+    pub fn new() -> Self {
+        ReducerChain { reducers: vec![] }
+    }
+
+    /// Append a reducer to the end of the chain.
+    pub fn then<R>(mut self, reducer: R) -> Self
+        where R: 'static + FrameReducer + Send + Sync,
+    {
+        self.reducers.push(Box::new(reducer));
+        self
+    }
+}
+
+impl Default for ReducerChain {
+    /// Drops blanked travel, then collinear points.
+    fn default() -> Self {
+        ReducerChain::new()
+            .then(DropBlankedTravel)
+            .then(RemoveCollinear::default())
+    }
+}
+
+impl FrameReducer for ReducerChain {
+    fn reduce(&self, points: &[BeyondLaserPoint], max_points: usize) -> Vec<BeyondLaserPoint> {
+        let mut points = points.to_vec();
+        for reducer in &self.reducers {
+            if points.len() <= max_points {
+                break;
+            }
+            points = reducer.reduce(&points, max_points);
+        }
+        points
+    }
+}
+
+/// Keep `max_points` evenly spaced points, including the first and last.
+fn resample(points: &[BeyondLaserPoint], max_points: usize) -> Vec<BeyondLaserPoint> {
+    match max_points {
+        0 => vec![],
+        1 => vec![points[0]],
+        _ => {
+            let last = points.len() - 1;
+            (0..max_points).map(|i| points[i * last / (max_points - 1)]).collect()
+        },
+    }
+}
+
+/// The distance from `point` to the line segment between `a` and `b`.
+fn distance_to_segment(point: &BeyondLaserPoint,
+                       a: &BeyondLaserPoint,
+                       b: &BeyondLaserPoint) -> f32
+{
+    let (p, a, b) = (point.position(), a.position(), b.position());
+    let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let ap = [p[0] - a[0], p[1] - a[1], p[2] - a[2]];
+    let len_sq = ab[0] * ab[0] + ab[1] * ab[1] + ab[2] * ab[2];
+    let t = if len_sq > 0.0 {
+        ((ap[0] * ab[0] + ap[1] * ab[1] + ap[2] * ab[2]) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let d = [ap[0] - ab[0] * t, ap[1] - ab[1] * t, ap[2] - ab[2] * t];
+    (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+}
+
+#[test]
+fn test_reducers() {
+    let lit = |x: f32| BeyondLaserPoint::from_units(x, 0.0, 0.0, 255, 255, 255);
+    let blank = |x: f32| BeyondLaserPoint::from_units(x, 0.0, 0.0, 0, 0, 0);
+
+    // A straight lit line only needs its ends.
+    let line: Vec<_> = (0..100).map(|i| lit(i as f32)).collect();
+    let reduced = RemoveCollinear::default().reduce(&line, 10);
+    assert_eq!(reduced, vec![line[0], line[99]]);
+
+    // Blank travel keeps only the points where the beam turns off and on.
+    let mut frame = vec![lit(0.0)];
+    frame.extend((1..50).map(|i| blank(i as f32)));
+    frame.push(lit(50.0));
+    let reduced = DropBlankedTravel.reduce(&frame, 10);
+    assert_eq!(reduced, vec![lit(0.0), blank(1.0), blank(49.0), lit(50.0)]);
+
+    // Uniform resampling fits the budget and keeps blanking transitions.
+    let frame: Vec<_> = (0..1000)
+        .map(|i| if i % 100 < 10 { blank(i as f32) } else { lit(i as f32) })
+        .collect();
+    let reduced = UniformResample.reduce(&frame, 100);
+    assert!(reduced.len() <= 100);
+    for i in (0..1000).filter(|i| i % 100 == 9 || i % 100 == 10) {
+        assert!(reduced.contains(&frame[i]));
+    }
+    assert_eq!(reduced.first(), frame.first());
+    assert_eq!(reduced.last(), frame.last());
+
+    // Frames that fit are left alone, while the rest always fit.
+    let frame: Vec<_> = (0..20_000).map(|i| lit((i % 7) as f32 * 1000.0)).collect();
+    assert_eq!(reduce_frame(&ReducerChain::default(), &frame[..10], 10).len(), 10);
+    assert!(reduce_frame(&ReducerChain::default(), &frame, 8192).len() <= 8192);
+}
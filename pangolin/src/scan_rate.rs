@@ -0,0 +1,91 @@
+use {Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// The rate at which a projector scans the points of a frame.
+///
+/// The SDK overloads a single `ARate` integer for both forms: positive values are a percentage of
+/// the projector's default scan rate while negative values are an absolute rate in points per
+/// second. `ScanRate` makes the choice explicit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScanRate {
+    /// A percentage of the projector's default scan rate, e.g. `Percent(100)`.
+    Percent(u16),
+    /// An absolute scan rate in points per second, e.g. `PointsPerSecond(30_000)`.
+    PointsPerSecond(u32),
+}
+
+impl ScanRate {
+    /// Encode the rate as the SDK's `ARate` argument.
+    ///
+    /// Returns an error if the rate is zero or too large to be represented.
+    pub fn to_sdk(&self) -> Result<i32> {
+        match *self {
+            ScanRate::Percent(percent) if percent > 0 => Ok(percent as i32),
+            ScanRate::PointsPerSecond(pps) if pps > 0 && pps <= i32::MAX as u32 =>
+                Ok(-(pps as i32)),
+            rate => Err(Error::InvalidScanRate(rate.to_string())),
+        }
+    }
+
+    /// Decode the SDK's `ARate` argument, returning `None` if it is zero or out of range.
+    pub fn from_sdk(rate: i32) -> Option<Self> {
+        if rate > 0 && rate <= u16::MAX as i32 {
+            Some(ScanRate::Percent(rate as u16))
+        } else if rate < 0 && rate != i32::MIN {
+            Some(ScanRate::PointsPerSecond(-rate as u32))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for ScanRate {
+    /// The projector's default scan rate.
+    fn default() -> Self {
+        ScanRate::Percent(100)
+    }
+}
+
+impl fmt::Display for ScanRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScanRate::Percent(percent) => write!(f, "{}%", percent),
+            ScanRate::PointsPerSecond(pps) => write!(f, "{}pps", pps),
+        }
+    }
+}
+
+impl FromStr for ScanRate {
+    type Err = Error;
+
+    /// Parses either a percentage such as `"100%"` or a rate such as `"30000pps"`.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let invalid = || Error::InvalidScanRate(s.to_string());
+        let rate = if let Some(percent) = s.strip_suffix('%') {
+            ScanRate::Percent(percent.trim().parse().map_err(|_| invalid())?)
+        } else if let Some(pps) = s.strip_suffix("pps") {
+            ScanRate::PointsPerSecond(pps.trim().parse().map_err(|_| invalid())?)
+        } else {
+            return Err(invalid());
+        };
+        rate.to_sdk()?;
+        Ok(rate)
+    }
+}
+
+#[test]
+fn test_scan_rate() {
+    assert_eq!(ScanRate::Percent(100).to_sdk().unwrap(), 100);
+    assert_eq!(ScanRate::PointsPerSecond(30_000).to_sdk().unwrap(), -30_000);
+    assert!(ScanRate::Percent(0).to_sdk().is_err());
+    assert!(ScanRate::PointsPerSecond(0).to_sdk().is_err());
+    assert!(ScanRate::PointsPerSecond(u32::MAX).to_sdk().is_err());
+    assert_eq!(ScanRate::from_sdk(-30_000), Some(ScanRate::PointsPerSecond(30_000)));
+    assert_eq!(ScanRate::from_sdk(0), None);
+    assert_eq!("80%".parse::<ScanRate>().unwrap(), ScanRate::Percent(80));
+    assert_eq!("30000pps".parse::<ScanRate>().unwrap(), ScanRate::PointsPerSecond(30_000));
+    assert!("30000".parse::<ScanRate>().is_err());
+    assert!("0%".parse::<ScanRate>().is_err());
+}
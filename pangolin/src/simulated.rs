@@ -0,0 +1,339 @@
+use {BeyondLaserPoint, LaserBackend, Result, MAX_NUM_POINTS};
+use error;
+use image::{ImageKind, ImageName};
+use std::sync::Mutex;
+use zone::ZoneArray;
+
+/// An in-memory stand-in for BEYONDIO.dll and a running instance of Beyond.
+///
+/// Follows the behaviour described in the Beyond SDK documentation:
+///
+/// - Creating an image whose name is already used by another image of the same kind is ignored.
+/// - Creating an image with an out-of-range zone or projector index is ignored.
+/// - Frames with more than `MAX_NUM_POINTS` points are rejected.
+/// - Each SDK image buffers only the most recent frame sent to it.
+/// - All calls that would need to message Beyond fail while it is not ready.
+///
+/// Calls succeed when Beyond received the message, even if it chose to ignore it.
+pub struct SimulatedBeyond {
+    state: Mutex<State>,
+}
+
+/// A frame as received by a simulated SDK image.
+#[derive(Clone, Debug)]
+pub struct SimulatedFrame {
+    pub points: Vec<BeyondLaserPoint>,
+    /// Zero-based zone indices, decoded from the zone array in order.
+    pub zone_indices: Vec<u8>,
+    /// The raw `ARate` argument. See `ScanRate::from_sdk`.
+    pub scan_rate: i32,
+}
+
+struct State {
+    started: bool,
+    ready: bool,
+    laser_output_enabled: bool,
+    projector_count: i32,
+    zone_count: i32,
+    zone_images: Vec<Image>,
+    projector_images: Vec<Image>,
+}
+
+struct Image {
+    name: ImageName,
+    index: i32,
+    frame: Option<SimulatedFrame>,
+}
+
+/// The value returned by `get_dll_version`, as documented by the SDK.
+const DLL_VERSION: i32 = 100;
+/// The value returned by `get_beyond_version` once started, as documented by the SDK.
+const BEYOND_VERSION: i32 = 712;
+
+impl SimulatedBeyond {
+    /// A started and ready instance of Beyond with the given number of projectors and zones.
+    pub fn new(projector_count: i32, zone_count: i32) -> Self {
+        let state = State {
+            started: true,
+            ready: true,
+            laser_output_enabled: false,
+            projector_count,
+            zone_count,
+            zone_images: vec![],
+            projector_images: vec![],
+        };
+        SimulatedBeyond { state: Mutex::new(state) }
+    }
+
+    /// Simulate BEYOND.EXE starting or exiting.
+    ///
+    /// Exiting also clears the ready state, all SDK images and the laser output state.
+    pub fn set_started(&self, started: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.started = started;
+        if !started {
+            state.ready = false;
+            state.laser_output_enabled = false;
+            state.zone_images.clear();
+            state.projector_images.clear();
+        }
+    }
+
+    /// Simulate Beyond finishing (or not yet finishing) its startup. Ready implies started.
+    pub fn set_ready(&self, ready: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.ready = ready;
+        if ready {
+            state.started = true;
+        }
+    }
+
+    pub fn laser_output_enabled(&self) -> bool {
+        self.state.lock().unwrap().laser_output_enabled
+    }
+
+    /// The names of all zone images in creation order, paired with their zone index.
+    pub fn zone_images(&self) -> Vec<(ImageName, i32)> {
+        let state = self.state.lock().unwrap();
+        state.zone_images.iter().map(|img| (img.name.clone(), img.index)).collect()
+    }
+
+    /// The names of all projector images in creation order, paired with their projector index.
+    pub fn projector_images(&self) -> Vec<(ImageName, i32)> {
+        let state = self.state.lock().unwrap();
+        state.projector_images.iter().map(|img| (img.name.clone(), img.index)).collect()
+    }
+
+    /// The most recent frame received by the image with the given name.
+    pub fn image_frame(&self, image_name: &ImageName) -> Option<SimulatedFrame> {
+        let state = self.state.lock().unwrap();
+        state.zone_images.iter()
+            .chain(state.projector_images.iter())
+            .find(|img| img.name == *image_name)
+            .and_then(|img| img.frame.clone())
+    }
+
+    /// The most recent frames of all images whose frames are addressed to the given zone.
+    pub fn zone_frames(&self, zone_index: u8) -> Vec<SimulatedFrame> {
+        let state = self.state.lock().unwrap();
+        state.zone_images.iter()
+            .chain(state.projector_images.iter())
+            .filter_map(|img| img.frame.as_ref())
+            .filter(|frame| frame.zone_indices.contains(&zone_index))
+            .cloned()
+            .collect()
+    }
+}
+
+impl State {
+    fn create_image(&mut self, kind: ImageKind, index: i32, image_name: &ImageName) -> i32 {
+        if !self.ready {
+            return 0;
+        }
+        let (count, images) = match kind {
+            ImageKind::Zone => (self.zone_count, &mut self.zone_images),
+            ImageKind::Projector => (self.projector_count, &mut self.projector_images),
+        };
+        let in_range = index >= 0 && index < count;
+        let exists = images.iter().any(|img| img.name == *image_name);
+        if in_range && !exists {
+            images.push(Image { name: image_name.clone(), index, frame: None });
+        }
+        1
+    }
+
+    fn delete_image(&mut self, kind: ImageKind, image_name: &ImageName) -> i32 {
+        if !self.ready {
+            return 0;
+        }
+        let images = match kind {
+            ImageKind::Zone => &mut self.zone_images,
+            ImageKind::Projector => &mut self.projector_images,
+        };
+        images.retain(|img| img.name != *image_name);
+        1
+    }
+
+    fn send_frame(&mut self,
+                  image_name: &ImageName,
+                  laser_points: &[BeyondLaserPoint],
+                  zone_array: &ZoneArray,
+                  scan_rate: i32) -> i32
+    {
+        if !self.ready || laser_points.len() > MAX_NUM_POINTS {
+            return 0;
+        }
+        let State { ref mut zone_images, ref mut projector_images, .. } = *self;
+        let image = zone_images.iter_mut()
+            .chain(projector_images.iter_mut())
+            .find(|img| img.name == *image_name);
+        match image {
+            Some(img) => {
+                img.frame = Some(SimulatedFrame {
+                    points: laser_points.to_vec(),
+                    zone_indices: zone_array.zone_indices(),
+                    scan_rate,
+                });
+                1
+            },
+            None => 0,
+        }
+    }
+}
+
+impl LaserBackend for SimulatedBeyond {
+    fn beyond_exe_started(&self) -> Result<bool> {
+        Ok(self.state.lock().unwrap().started)
+    }
+
+    fn beyond_exe_ready(&self) -> Result<bool> {
+        Ok(self.state.lock().unwrap().ready)
+    }
+
+    fn enable_laser_output(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.ready {
+            state.laser_output_enabled = true;
+        }
+        error::check("ldbEnableLaserOutput", state.ready as i32)
+    }
+
+    fn disable_laser_output(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.ready {
+            state.laser_output_enabled = false;
+        }
+        error::check("ldbDisableLaserOutput", state.ready as i32)
+    }
+
+    fn blackout(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.ready {
+            let State { ref mut zone_images, ref mut projector_images, .. } = *state;
+            for img in zone_images.iter_mut().chain(projector_images.iter_mut()) {
+                img.frame = None;
+            }
+        }
+        error::check("ldbBlackout", state.ready as i32)
+    }
+
+    fn get_dll_version(&self) -> Result<i32> {
+        Ok(DLL_VERSION)
+    }
+
+    fn get_beyond_version(&self) -> Result<i32> {
+        Ok(if self.state.lock().unwrap().started { BEYOND_VERSION } else { 0 })
+    }
+
+    fn get_projector_count(&self) -> Result<i32> {
+        let state = self.state.lock().unwrap();
+        Ok(if state.ready { state.projector_count } else { 0 })
+    }
+
+    fn get_zone_count(&self) -> Result<i32> {
+        let state = self.state.lock().unwrap();
+        Ok(if state.ready { state.zone_count } else { 0 })
+    }
+
+    fn insert_zone_image(&self, zone_index: i32, image_name: &ImageName) -> Result<()> {
+        let code = self.state.lock().unwrap().create_image(ImageKind::Zone, zone_index, image_name);
+        error::check("ldbCreateZoneImage", code)
+    }
+
+    fn insert_projector_image(&self, projector_index: i32, image_name: &ImageName) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let code = state.create_image(ImageKind::Projector, projector_index, image_name);
+        error::check("ldbCreateProjectorImage", code)
+    }
+
+    fn delete_zone_image(&self, image_name: &ImageName) -> Result<()> {
+        let code = self.state.lock().unwrap().delete_image(ImageKind::Zone, image_name);
+        error::check("ldbDeleteZoneImage", code)
+    }
+
+    fn delete_projector_image(&self, image_name: &ImageName) -> Result<()> {
+        let code = self.state.lock().unwrap().delete_image(ImageKind::Projector, image_name);
+        error::check("ldbDeleteProjectorImage", code)
+    }
+
+    fn send_frame(&self,
+                  image_name: &ImageName,
+                  laser_points: &[BeyondLaserPoint],
+                  zone_array: &ZoneArray,
+                  scan_rate: i32) -> Result<()>
+    {
+        let mut state = self.state.lock().unwrap();
+        let code = state.send_frame(image_name, laser_points, zone_array, scan_rate);
+        error::check("ldbSendFrameToImage", code)
+    }
+}
+
+#[test]
+fn test_simulated_images() {
+    use ScanRate;
+    use zone::ZoneSet;
+    let beyond = SimulatedBeyond::new(1, 4);
+    let output1 = ImageName::new("/output1").unwrap();
+    let output2 = ImageName::new("/output2").unwrap();
+    assert!(beyond.insert_zone_image(0, &output1).is_ok());
+    // Duplicate names and out-of-range zones are ignored.
+    assert!(beyond.insert_zone_image(1, &output1).is_ok());
+    assert!(beyond.insert_zone_image(4, &output2).is_ok());
+    assert_eq!(beyond.zone_images(), vec![(output1.clone(), 0)]);
+
+    let points = vec![BeyondLaserPoint::new(0.5, 0.5, 0.5, 255, 0, 0); 3];
+    let zones = ZoneSet::from_indices(&[3, 1]).unwrap();
+    let scan_rate = ScanRate::PointsPerSecond(30_000);
+    assert!(beyond.send_frame_to_image(&output1, &points, &zones, scan_rate).is_ok());
+    let frame = beyond.image_frame(&output1).unwrap();
+    assert_eq!(frame.points.len(), 3);
+    assert_eq!(frame.zone_indices, vec![3, 1]);
+    assert_eq!(frame.scan_rate, -30_000);
+    assert_eq!(beyond.zone_frames(1).len(), 1);
+    assert!(beyond.zone_frames(0).is_empty());
+
+    // Unknown image names are rejected.
+    let zone0 = ZoneSet::single(0).unwrap();
+    assert!(beyond.send_frame_to_image(&output2, &points, &zone0, ScanRate::default()).is_err());
+
+    assert!(beyond.delete_zone_image(&output1).is_ok());
+    assert!(beyond.zone_images().is_empty());
+
+    // Images created via `create_zone_image` are deleted on drop.
+    {
+        let image = beyond.create_zone_image(2, output2.clone()).unwrap();
+        let zones = ZoneSet::single(2).unwrap();
+        assert!(image.send_frame(&points, &zones, ScanRate::Percent(50)).is_ok());
+        // Zones that Beyond does not have are rejected against the cached zone count.
+        assert_eq!(image.zone_count(), 4);
+        let zone4 = ZoneSet::single(4).unwrap();
+        assert!(image.send_frame(&points, &zone4, ScanRate::default()).is_err());
+        assert_eq!(beyond.zone_images(), vec![(output2.clone(), 2)]);
+    }
+    assert!(beyond.zone_images().is_empty());
+}
+
+#[test]
+fn test_simulated_ready_state() {
+    use zone::ZoneSet;
+    let beyond = SimulatedBeyond::new(1, 1);
+    let output1 = ImageName::new("/output1").unwrap();
+    beyond.set_ready(false);
+    assert!(beyond.beyond_exe_started().unwrap());
+    assert!(beyond.enable_laser_output().is_err());
+    assert!(beyond.insert_zone_image(0, &output1).is_err());
+    assert_eq!(beyond.get_zone_count().unwrap(), 0);
+
+    beyond.set_ready(true);
+    assert!(beyond.enable_laser_output().is_ok());
+    assert!(beyond.laser_output_enabled());
+
+    let points = vec![BeyondLaserPoint::new(0.5, 0.5, 0.5, 0, 0, 0); MAX_NUM_POINTS + 1];
+    let zone_array = ZoneArray::new(&ZoneSet::single(0).unwrap());
+    let _image = beyond.create_zone_image(0, output1.clone()).unwrap();
+    assert!(beyond.send_frame(&output1, &points, &zone_array, 100).is_err());
+
+    beyond.set_started(false);
+    assert!(!beyond.beyond_exe_ready().unwrap());
+    assert!(beyond.zone_images().is_empty());
+}
@@ -0,0 +1,178 @@
+use {BeyondLaserPoint, ImageName, LaserBackend, Result, ScanRate, ZoneSet};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The number of reports buffered before further reports are dropped.
+const REPORT_CAPACITY: usize = 1024;
+
+/// Submits frames to SDK images from a background thread.
+///
+/// `ldbSendFrameToImage` blocks while Beyond copies the frame, which would otherwise stall the
+/// caller. A single thread sends every image's frame in turn, so a slow send for one image still
+/// delays the images after it, but the caller is never blocked. Each image has a single slot
+/// holding its most recently submitted frame. If a newer frame arrives before the slot is drained,
+/// the older frame is replaced and never sent. Slots are drained in the order in which they were
+/// first filled.
+///
+/// The outcome of each send is reported via `reports`.
+pub struct FrameSubmitter {
+    shared: Arc<Shared>,
+    reports: mpsc::Receiver<SubmitReport>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// The outcome of sending a single frame to an SDK image.
+#[derive(Debug)]
+pub struct SubmitReport {
+    pub image_name: ImageName,
+    /// The time from the call to `submit` until the send completed.
+    pub latency: Duration,
+    /// The time spent within `send_frame_to_image`.
+    pub send_duration: Duration,
+    /// The number of older frames for this image that were replaced before this one was sent.
+    pub replaced: u32,
+    pub result: Result<()>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+struct State {
+    pending: Vec<(ImageName, Pending)>,
+    shutdown: bool,
+}
+
+struct Pending {
+    points: Vec<BeyondLaserPoint>,
+    zones: ZoneSet,
+    scan_rate: ScanRate,
+    submitted_at: Instant,
+    replaced: u32,
+}
+
+impl FrameSubmitter {
+    /// Spawn a thread that sends submitted frames to the given backend.
+    pub fn new<B>(backend: Arc<B>) -> Self
+        where B: 'static + LaserBackend + Send + Sync,
+    {
+        let state = State { pending: vec![], shutdown: false };
+        let shared = Arc::new(Shared { state: Mutex::new(state), condvar: Condvar::new() });
+        let (report_sender, reports) = mpsc::sync_channel(REPORT_CAPACITY);
+        let thread_shared = shared.clone();
+        let thread = thread::Builder::new()
+            .name("pangolin-frame-submitter".into())
+            .spawn(move || run(&*backend, &thread_shared, &report_sender))
+            .expect("failed to spawn frame submitter thread");
+        FrameSubmitter { shared, reports, thread: Some(thread) }
+    }
+
+    /// Replace the pending frame for the named image, returning immediately.
+    pub fn submit(&self,
+                  image_name: &ImageName,
+                  points: Vec<BeyondLaserPoint>,
+                  zones: ZoneSet,
+                  scan_rate: ScanRate)
+    {
+        let submitted_at = Instant::now();
+        let mut pending = Pending { points, zones, scan_rate, submitted_at, replaced: 0 };
+        let mut state = self.shared.state.lock().unwrap();
+        match state.pending.iter_mut().find(|&&mut (ref name, _)| name == image_name) {
+            Some(&mut (_, ref mut slot)) => {
+                pending.replaced = slot.replaced + 1;
+                *slot = pending;
+            },
+            None => state.pending.push((image_name.clone(), pending)),
+        }
+        self.shared.condvar.notify_one();
+    }
+
+    /// Reports for each frame sent so far.
+    ///
+    /// If more than 1024 reports are left unread, further reports are dropped until some are read.
+    pub fn reports(&self) -> &mpsc::Receiver<SubmitReport> {
+        &self.reports
+    }
+}
+
+impl Drop for FrameSubmitter {
+    /// Discards any pending frames and waits for an in-progress send to complete.
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.condvar.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Drain pending frames until shutdown.
+fn run<B>(backend: &B, shared: &Shared, reports: &mpsc::SyncSender<SubmitReport>)
+    where B: ?Sized + LaserBackend,
+{
+    loop {
+        let (image_name, pending) = {
+            let mut state = shared.state.lock().unwrap();
+            loop {
+                if state.shutdown {
+                    return;
+                }
+                if !state.pending.is_empty() {
+                    break state.pending.remove(0);
+                }
+                state = shared.condvar.wait(state).unwrap();
+            }
+        };
+        let start = Instant::now();
+        let result = backend.send_frame_to_image(&image_name,
+                                                 &pending.points,
+                                                 &pending.zones,
+                                                 pending.scan_rate);
+        let report = SubmitReport {
+            image_name,
+            latency: pending.submitted_at.elapsed(),
+            send_duration: start.elapsed(),
+            replaced: pending.replaced,
+            result,
+        };
+        // Nobody is reading reports if the buffer is full, so they may be dropped.
+        if let Err(mpsc::TrySendError::Disconnected(_)) = reports.try_send(report) {
+            return;
+        }
+    }
+}
+
+#[test]
+fn test_frame_submitter() {
+    use SimulatedBeyond;
+
+    let beyond = Arc::new(SimulatedBeyond::new(1, 1));
+    let output1 = ImageName::new("/output1").unwrap();
+    let output2 = ImageName::new("/output2").unwrap();
+    beyond.insert_zone_image(0, &output1).unwrap();
+    let submitter = FrameSubmitter::new(beyond.clone());
+    let zones = ZoneSet::single(0).unwrap();
+    let timeout = Duration::from_secs(5);
+
+    // Only the latest of each burst of frames is guaranteed to be sent.
+    for i in 1..4 {
+        let points = vec![BeyondLaserPoint::new(0.5, 0.5, 0.5, 0, 0, 0); i];
+        submitter.submit(&output1, points, zones.clone(), ScanRate::default());
+    }
+    let mut frames = 0;
+    while frames < 3 {
+        let report = submitter.reports().recv_timeout(timeout).unwrap();
+        assert_eq!(report.image_name, output1);
+        assert!(report.result.is_ok());
+        frames += 1 + report.replaced;
+    }
+    assert_eq!(beyond.image_frame(&output1).unwrap().points.len(), 3);
+
+    // Failures are reported rather than returned.
+    submitter.submit(&output2, vec![], zones, ScanRate::default());
+    let report = submitter.reports().recv_timeout(timeout).unwrap();
+    assert_eq!(report.image_name, output2);
+    assert!(report.result.is_err());
+}
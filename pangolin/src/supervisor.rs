@@ -0,0 +1,301 @@
+use {Image, ImageKind, ImageName, LaserBackend, Result, ZoneSet};
+use std::sync::mpsc;
+
+/// The state of the connection to Beyond, as observed by a `Supervisor`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// BEYOND.EXE is not running.
+    Stopped,
+    /// BEYOND.EXE is running but the SDK is not yet ready.
+    Starting,
+    /// The SDK is ready, but Beyond is too old or has too few projectors or zones for the
+    /// supervised images, so they have not been created.
+    Incompatible,
+    /// The SDK is ready and the supervised images have been created.
+    Ready,
+}
+
+/// An event emitted by a `Supervisor` to each of its subscribers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionEvent {
+    /// The connection moved to the given state.
+    StateChanged(ConnectionState),
+    /// Failed to create the named SDK image once Beyond became ready.
+    ImageFailed { name: ImageName, error: String },
+    /// Failed to enable the laser output once Beyond became ready.
+    EnableFailed(String),
+    /// Failed to query whether Beyond is started or ready. The state is left unchanged.
+    QueryFailed(String),
+    /// Refused to create the images and enable the laser output, for the given reason.
+    ArmRefused(String),
+}
+
+/// Watches the state of Beyond and recreates SDK images whenever it becomes ready.
+///
+/// SDK images only live as long as the instance of Beyond they were created within. If Beyond is
+/// restarted, the supervisor notices the transition back to ready on the next `poll`, recreates
+/// each of its images and enables the laser output again.
+///
+/// Before arming, the supervisor checks the versions reported by `BeyondInfo` and that Beyond
+/// has a projector and zone for the index of every supervised image, along with every zone that
+/// its frames are sent to. If not, it stays `Incompatible` and checks again on each `poll`.
+pub struct Supervisor<'a, B: 'a + ?Sized + LaserBackend> {
+    backend: &'a B,
+    specs: Vec<ImageSpec>,
+    images: Vec<Option<Image<'a, B>>>,
+    state: ConnectionState,
+    subscribers: Vec<mpsc::Sender<ConnectionEvent>>,
+}
+
+/// The arguments needed to recreate an SDK image, along with the zones its frames are sent to.
+struct ImageSpec {
+    kind: ImageKind,
+    index: i32,
+    name: ImageName,
+    zones: ZoneSet,
+}
+
+impl<'a, B: ?Sized + LaserBackend> Supervisor<'a, B> {
+    /// A supervisor with no images, which considers Beyond stopped until the first `poll`.
+    pub fn new(backend: &'a B) -> Self {
+        Supervisor {
+            backend,
+            specs: vec![],
+            images: vec![],
+            state: ConnectionState::Stopped,
+            subscribers: vec![],
+        }
+    }
+
+    /// Supervise an SDK image within the given projection zone, whose frames are sent to `zones`.
+    ///
+    /// Returns the index of the image for use with `image`. The image is created on the next
+    /// transition to `ConnectionState::Ready`.
+    pub fn add_zone_image(&mut self, zone_index: i32, name: ImageName, zones: ZoneSet) -> usize {
+        self.add_image(ImageKind::Zone, zone_index, name, zones)
+    }
+
+    /// Supervise an SDK image within the given projector. See `add_zone_image`.
+    pub fn add_projector_image(&mut self,
+                               projector_index: i32,
+                               name: ImageName,
+                               zones: ZoneSet) -> usize
+    {
+        self.add_image(ImageKind::Projector, projector_index, name, zones)
+    }
+
+    fn add_image(&mut self, kind: ImageKind, index: i32, name: ImageName, zones: ZoneSet)
+        -> usize
+    {
+        self.specs.push(ImageSpec { kind, index, name, zones });
+        self.images.push(None);
+        self.images.len() - 1
+    }
+
+    /// Receive every `ConnectionEvent` emitted from now on.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<ConnectionEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    pub fn backend(&self) -> &'a B {
+        self.backend
+    }
+
+    /// The state observed by the most recent `poll`.
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// The image at the given index, if it exists within the current instance of Beyond.
+    pub fn image(&self, index: usize) -> Option<&Image<'a, B>> {
+        self.images.get(index).and_then(|image| image.as_ref())
+    }
+
+    /// Query the state of Beyond, recreating images and enabling the laser output if it has just
+    /// become ready.
+    pub fn poll(&mut self) -> ConnectionState {
+        // A failed query says nothing about whether Beyond is still running, so keep the current
+        // state and its images rather than deleting them from a Beyond that may still be ready.
+        let mut state = match self.query_state() {
+            Ok(state) => state,
+            Err(err) => {
+                self.emit(ConnectionEvent::QueryFailed(err.to_string()));
+                return self.state;
+            },
+        };
+        let mut refusal = None;
+        if state == ConnectionState::Ready && self.state != ConnectionState::Ready {
+            if let Err(err) = self.check_compatible() {
+                state = ConnectionState::Incompatible;
+                refusal = Some(err);
+            }
+        }
+        if state == self.state {
+            return state;
+        }
+        if let Some(err) = refusal {
+            self.emit(ConnectionEvent::ArmRefused(err.to_string()));
+        }
+        if state == ConnectionState::Ready {
+            self.arm();
+        } else {
+            // Any images belonged to an instance of Beyond that is no longer ready.
+            for image in &mut self.images {
+                *image = None;
+            }
+        }
+        self.state = state;
+        self.emit(ConnectionEvent::StateChanged(state));
+        state
+    }
+
+    fn query_state(&self) -> Result<ConnectionState> {
+        if !self.backend.beyond_exe_started()? {
+            return Ok(ConnectionState::Stopped);
+        }
+        if !self.backend.beyond_exe_ready()? {
+            return Ok(ConnectionState::Starting);
+        }
+        Ok(ConnectionState::Ready)
+    }
+
+    /// Check that Beyond is recent enough and has the projectors and zones the images need,
+    /// both to be created within and to send their frames to.
+    fn check_compatible(&self) -> Result<()> {
+        let info = self.backend.beyond_info()?;
+        info.check_versions()?;
+        let required = |kind| {
+            self.specs.iter()
+                .filter(|spec| spec.kind == kind)
+                .map(|spec| spec.index + 1)
+                .max()
+                .unwrap_or(0)
+        };
+        let frame_zones_required = self.specs.iter()
+            .flat_map(|spec| spec.zones.indices())
+            .map(|&zone_index| zone_index as i32 + 1)
+            .max()
+            .unwrap_or(0);
+        let zones_required = required(ImageKind::Zone).max(frame_zones_required);
+        info.check_outputs(required(ImageKind::Projector), zones_required)
+    }
+
+    /// Create each image and enable the laser output.
+    fn arm(&mut self) {
+        let backend = self.backend;
+        for (spec, image) in self.specs.iter().zip(&mut self.images) {
+            let name = spec.name.clone();
+            let result = match spec.kind {
+                ImageKind::Zone => backend.create_zone_image(spec.index, name),
+                ImageKind::Projector => backend.create_projector_image(spec.index, name),
+            };
+            *image = match result {
+                Ok(created) => Some(created),
+                Err(err) => {
+                    let event = ConnectionEvent::ImageFailed {
+                        name: spec.name.clone(),
+                        error: err.to_string(),
+                    };
+                    emit(&mut self.subscribers, event);
+                    None
+                },
+            };
+        }
+        if let Err(err) = backend.enable_laser_output() {
+            self.emit(ConnectionEvent::EnableFailed(err.to_string()));
+        }
+    }
+
+    fn emit(&mut self, event: ConnectionEvent) {
+        emit(&mut self.subscribers, event);
+    }
+}
+
+/// Send the event to each subscriber, forgetting those that have hung up.
+fn emit(subscribers: &mut Vec<mpsc::Sender<ConnectionEvent>>, event: ConnectionEvent) {
+    subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+}
+
+#[test]
+fn test_supervisor_survives_restart() {
+    use SimulatedBeyond;
+
+    let beyond = SimulatedBeyond::new(1, 2);
+    let output1 = ImageName::new("/output1").unwrap();
+    let mut supervisor = Supervisor::new(&beyond);
+    let events = supervisor.subscribe();
+    let index = supervisor.add_zone_image(0, output1.clone(), ZoneSet::single(0).unwrap());
+    assert!(supervisor.image(index).is_none());
+
+    assert_eq!(supervisor.poll(), ConnectionState::Ready);
+    assert!(supervisor.image(index).is_some());
+    assert_eq!(beyond.zone_images(), vec![(output1.clone(), 0)]);
+    assert!(beyond.laser_output_enabled());
+
+    // Beyond exits, clearing its images, then restarts.
+    beyond.set_started(false);
+    assert_eq!(supervisor.poll(), ConnectionState::Stopped);
+    assert!(supervisor.image(index).is_none());
+    beyond.set_started(true);
+    assert_eq!(supervisor.poll(), ConnectionState::Starting);
+    beyond.set_ready(true);
+    assert_eq!(supervisor.poll(), ConnectionState::Ready);
+    assert_eq!(beyond.zone_images(), vec![(output1.clone(), 0)]);
+    assert!(beyond.laser_output_enabled());
+
+    let states: Vec<_> = events.try_iter().collect();
+    assert_eq!(states, vec![
+        ConnectionEvent::StateChanged(ConnectionState::Ready),
+        ConnectionEvent::StateChanged(ConnectionState::Stopped),
+        ConnectionEvent::StateChanged(ConnectionState::Starting),
+        ConnectionEvent::StateChanged(ConnectionState::Ready),
+    ]);
+}
+
+#[test]
+fn test_supervisor_refuses_too_few_zones() {
+    use SimulatedBeyond;
+
+    let beyond = SimulatedBeyond::new(1, 2);
+    let mut supervisor = Supervisor::new(&beyond);
+    let events = supervisor.subscribe();
+    let output3 = ImageName::new("/output3").unwrap();
+    let index = supervisor.add_zone_image(2, output3, ZoneSet::single(2).unwrap());
+
+    assert_eq!(supervisor.poll(), ConnectionState::Incompatible);
+    assert_eq!(supervisor.poll(), ConnectionState::Incompatible);
+    assert!(supervisor.image(index).is_none());
+    assert!(beyond.zone_images().is_empty());
+    assert!(!beyond.laser_output_enabled());
+
+    let events: Vec<_> = events.try_iter().collect();
+    assert_eq!(events, vec![
+        ConnectionEvent::ArmRefused("3 zones required but Beyond only has 2".to_string()),
+        ConnectionEvent::StateChanged(ConnectionState::Incompatible),
+    ]);
+}
+
+#[test]
+fn test_supervisor_refuses_missing_frame_zones() {
+    use SimulatedBeyond;
+
+    // As in the bridge, the image for output 5 is created within zone 4 but sends to zone 5.
+    let beyond = SimulatedBeyond::new(1, 5);
+    let mut supervisor = Supervisor::new(&beyond);
+    let events = supervisor.subscribe();
+    let output5 = ImageName::new("/output5").unwrap();
+    let index = supervisor.add_zone_image(4, output5, ZoneSet::single(5).unwrap());
+
+    assert_eq!(supervisor.poll(), ConnectionState::Incompatible);
+    assert!(supervisor.image(index).is_none());
+    assert!(beyond.zone_images().is_empty());
+    assert!(!beyond.laser_output_enabled());
+
+    let events: Vec<_> = events.try_iter().collect();
+    assert_eq!(events, vec![
+        ConnectionEvent::ArmRefused("6 zones required but Beyond only has 5".to_string()),
+        ConnectionEvent::StateChanged(ConnectionState::Incompatible),
+    ]);
+}
@@ -0,0 +1,123 @@
+use Result;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A source of "projector ticks": the moments at which Beyond finishes calculating the output for
+/// a projector and takes a copy of the frames within each SDK image.
+///
+/// Submitting frames immediately after a tick gives them the longest possible time to arrive
+/// before Beyond's next calculation.
+pub trait ProjectorTick {
+    /// Block until the next tick or until `timeout` has elapsed.
+    ///
+    /// Returns `true` if a tick occurred or `false` if the timeout elapsed first.
+    fn wait(&self, timeout: Duration) -> Result<bool>;
+}
+
+/// A `ProjectorTick` driven by an ordinary timer, ticking at a fixed interval.
+///
+/// Useful for pacing frames when no projector event is available, e.g. with `SimulatedBeyond`.
+pub struct TimerTick {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl TimerTick {
+    /// A timer whose first tick occurs one `interval` from now.
+    pub fn new(interval: Duration) -> Self {
+        TimerTick { interval, next: Mutex::new(Instant::now() + interval) }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl ProjectorTick for TimerTick {
+    fn wait(&self, timeout: Duration) -> Result<bool> {
+        let mut next = self.next.lock().unwrap();
+        let now = Instant::now();
+        if *next > now + timeout {
+            thread::sleep(timeout);
+            return Ok(false);
+        }
+        if *next > now {
+            thread::sleep(*next - now);
+        }
+        // Skip any ticks that were missed rather than firing them in a burst.
+        let now = Instant::now();
+        while *next <= now {
+            *next += self.interval;
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(windows)]
+pub use self::windows::ProjectorEvent;
+
+#[cfg(windows)]
+mod windows {
+    use {supported, Error, Pangolin, ProjectorTick, Result};
+    use std::marker::PhantomData;
+    use std::os::raw::c_void;
+    use std::time::Duration;
+
+    const WAIT_OBJECT_0: u32 = 0;
+    const WAIT_TIMEOUT: u32 = 0x102;
+
+    extern "system" {
+        fn WaitForSingleObject(handle: *mut c_void, millis: u32) -> u32;
+    }
+
+    /// The named event that Beyond pulses at the end of each calculation for a projector.
+    ///
+    /// The event handle is owned by BEYONDIO.dll and remains valid for the lifetime of the
+    /// `Pangolin` handle.
+    pub struct ProjectorEvent<'a> {
+        handle: *mut c_void,
+        _pangolin: PhantomData<&'a Pangolin>,
+    }
+
+    // Event handles may be waited upon from any thread.
+    unsafe impl<'a> Send for ProjectorEvent<'a> {}
+    unsafe impl<'a> Sync for ProjectorEvent<'a> {}
+
+    impl Pangolin {
+        /// The event pulsed by Beyond at the end of each calculation for the given projector.
+        pub fn projector_event<'a>(&'a self, projector_index: i32) -> Result<ProjectorEvent<'a>> {
+            let function = supported(self.ldb_get_projector_event, "ldbGetProjectorEvent")?;
+            let handle = unsafe { function(projector_index) };
+            if handle.is_null() {
+                return Err(Error::Failed { function: "ldbGetProjectorEvent", code: 0 });
+            }
+            Ok(ProjectorEvent { handle, _pangolin: PhantomData })
+        }
+    }
+
+    impl<'a> ProjectorTick for ProjectorEvent<'a> {
+        fn wait(&self, timeout: Duration) -> Result<bool> {
+            let millis = timeout.as_secs()
+                .saturating_mul(1_000)
+                .saturating_add(timeout.subsec_millis() as u64)
+                .min(u32::MAX as u64 - 1) as u32;
+            match unsafe { WaitForSingleObject(self.handle, millis) } {
+                WAIT_OBJECT_0 => Ok(true),
+                WAIT_TIMEOUT => Ok(false),
+                code => Err(Error::Failed { function: "WaitForSingleObject", code: code as i32 }),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_timer_tick() {
+    let tick = TimerTick::new(Duration::from_millis(20));
+    // The first tick is further away than the timeout.
+    assert!(!tick.wait(Duration::from_millis(1)).unwrap());
+    let start = Instant::now();
+    assert!(tick.wait(Duration::from_secs(1)).unwrap());
+    assert!(tick.wait(Duration::from_secs(1)).unwrap());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
@@ -0,0 +1,88 @@
+use {supported, Error, Pangolin, Result};
+use std::time::Duration;
+
+/// A Beyond timecode value, measured in milliseconds.
+///
+/// Beyond accepts timecode from multiple sources (MIDI, SMPTE, ArtNet, etc) which all end up at
+/// the same place before being delivered to the Timeline, Grid or Play list.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timecode {
+    millis: u32,
+}
+
+impl Timecode {
+    /// The largest timecode that may be passed to the SDK.
+    pub const MAX: Timecode = Timecode { millis: i32::MAX as u32 };
+
+    /// Returns an error if `millis` is greater than `Timecode::MAX`.
+    pub fn from_millis(millis: u32) -> Result<Self> {
+        Timecode::from_duration(Duration::from_millis(millis as u64))
+    }
+
+    /// Returns an error if `duration` is greater than `Timecode::MAX`. Sub-millisecond precision
+    /// is truncated.
+    pub fn from_duration(duration: Duration) -> Result<Self> {
+        let millis = duration.as_millis();
+        if millis > Timecode::MAX.millis as u128 {
+            return Err(Error::InvalidTimecode(duration));
+        }
+        Ok(Timecode { millis: millis as u32 })
+    }
+
+    /// Interpret the millisecond value returned by the given SDK function.
+    pub(crate) fn from_sdk(function: &'static str, millis: i32) -> Result<Self> {
+        if millis < 0 {
+            return Err(Error::UnexpectedReturn { function, code: millis });
+        }
+        Ok(Timecode { millis: millis as u32 })
+    }
+
+    pub fn as_millis(&self) -> u32 {
+        self.millis
+    }
+
+    pub fn to_duration(&self) -> Duration {
+        Duration::from_millis(self.millis as u64)
+    }
+}
+
+impl From<Timecode> for Duration {
+    fn from(timecode: Timecode) -> Self {
+        timecode.to_duration()
+    }
+}
+
+impl Pangolin {
+    /// The last timecode value received by Beyond from any of its timecode sources.
+    pub fn get_time_code(&self) -> Result<Timecode> {
+        let function = supported(self.ldb_get_time_code, "ldbGetTimeCode")?;
+        let millis = unsafe { function() };
+        Timecode::from_sdk("ldbGetTimeCode", millis)
+    }
+
+    /// Supply a timecode value to Beyond, as though it came from any other timecode source.
+    pub fn set_time_code(&self, timecode: Timecode) -> Result<()> {
+        let millis = timecode.millis as i32;
+        let function = supported(self.ldb_set_time_code, "ldbSetTimeCode")?;
+        let code = unsafe { function(millis) };
+        // The result must be equal to the value supplied.
+        if code != millis {
+            return Err(Error::Failed { function: "ldbSetTimeCode", code });
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_timecode() {
+    let timecode = Timecode::from_duration(Duration::new(61, 500_999_999)).unwrap();
+    assert_eq!(timecode.as_millis(), 61_500);
+    assert_eq!(Duration::from(timecode), Duration::from_millis(61_500));
+    assert_eq!(Timecode::from_millis(i32::MAX as u32).unwrap(), Timecode::MAX);
+    let too_long = Duration::from_millis(i32::MAX as u64 + 1);
+    match Timecode::from_millis(i32::MAX as u32 + 1) {
+        Err(Error::InvalidTimecode(duration)) => assert_eq!(duration, too_long),
+        result => panic!("expected an invalid timecode, found {:?}", result),
+    }
+    assert!(Timecode::from_duration(Duration::from_secs(u64::MAX)).is_err());
+}
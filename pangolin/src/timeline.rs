@@ -0,0 +1,85 @@
+use {error, supported, Pangolin, Result, Timecode};
+
+/// Control over the show loaded in Beyond's timeline editor.
+///
+/// Timeline positions and durations are measured in milliseconds, the same as `Timecode`.
+///
+/// The SDK doesn't document the values returned by `ldbTimelinePlay`, `ldbTimelineStop`,
+/// `ldbTimelineSetPos` and `ldbTimelineSetOnline`, so they are ignored and those calls only fail
+/// if the function is unsupported.
+pub struct Timeline<'a> {
+    pangolin: &'a Pangolin,
+}
+
+impl Pangolin {
+    /// Access the timeline editor.
+    pub fn timeline<'a>(&'a self) -> Timeline<'a> {
+        Timeline { pangolin: self }
+    }
+}
+
+impl<'a> Timeline<'a> {
+    /// Start playback of the current show. Does nothing if already playing.
+    pub fn play(&self) -> Result<()> {
+        let function = supported(self.pangolin.ldb_timeline_play, "ldbTimelinePlay")?;
+        unsafe {
+            function();
+        }
+        Ok(())
+    }
+
+    /// Stop playback of the current show. Does nothing if already stopped or paused.
+    pub fn stop(&self) -> Result<()> {
+        let function = supported(self.pangolin.ldb_timeline_stop, "ldbTimelineStop")?;
+        unsafe {
+            function();
+        }
+        Ok(())
+    }
+
+    pub fn is_playing(&self) -> Result<bool> {
+        let function = supported(self.pangolin.ldb_timeline_get_playing, "ldbTimelineGetPlaying")?;
+        let code = unsafe { function() };
+        error::check_bool("ldbTimelineGetPlaying", code)
+    }
+
+    /// Move the timeline to the given position.
+    pub fn seek(&self, position: Timecode) -> Result<()> {
+        let function = supported(self.pangolin.ldb_timeline_set_pos, "ldbTimelineSetPos")?;
+        unsafe {
+            function(position.as_millis() as i32);
+        }
+        Ok(())
+    }
+
+    /// The current position, both during playback and while editing or paused.
+    pub fn position(&self) -> Result<Timecode> {
+        let function = supported(self.pangolin.ldb_timeline_get_pos, "ldbTimelineGetPos")?;
+        let millis = unsafe { function() };
+        Timecode::from_sdk("ldbTimelineGetPos", millis)
+    }
+
+    /// The duration of the current show.
+    pub fn duration(&self) -> Result<Timecode> {
+        let function =
+            supported(self.pangolin.ldb_timeline_get_duration, "ldbTimelineGetDuration")?;
+        let millis = unsafe { function() };
+        Timecode::from_sdk("ldbTimelineGetDuration", millis)
+    }
+
+    /// Enable (online) or disable (offline) laser output from the timeline editor.
+    pub fn set_online(&self, online: bool) -> Result<()> {
+        let function = supported(self.pangolin.ldb_timeline_set_online, "ldbTimelineSetOnline")?;
+        unsafe {
+            function(online as i32);
+        }
+        Ok(())
+    }
+
+    /// Whether or not laser output from the timeline editor is enabled.
+    pub fn is_online(&self) -> Result<bool> {
+        let function = supported(self.pangolin.ldb_timeline_get_online, "ldbTimelineGetOnline")?;
+        let code = unsafe { function() };
+        error::check_bool("ldbTimelineGetOnline", code)
+    }
+}
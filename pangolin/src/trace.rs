@@ -0,0 +1,582 @@
+//! Recording and replaying the calls made to a `LaserBackend`.
+//!
+//! A trace begins with a header holding the wall-clock time at which recording started, so that
+//! the relative time of each record can be matched against show time.
+//!
+//! Only the `LaserBackend` functions are recorded. The `Pangolin` functions for timecode, MIDI,
+//! DMX, channels, the timeline and Kinect are not part of the trait and so never appear in a trace.
+
+use {BeyondLaserPoint, Error, ImageName, LaserBackend, Result, ZoneArray, ZoneSet, MAX_NUM_POINTS};
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The bytes at the start of every trace file, including the format version.
+const MAGIC: &[u8; 8] = b"PGTRACE2";
+
+/// A `LaserBackend` that records every call made to the backend it wraps.
+///
+/// Each call is written to the trace as it completes, along with the time since recording began
+/// and the result. Frames are recorded exactly as they are handed to `send_frame`, i.e. after zone
+/// validation and reduction.
+///
+/// Failures to write the trace never affect the wrapped calls. The first such failure is returned
+/// by `finish` instead.
+pub struct TraceRecorder<B, W> {
+    backend: B,
+    start: Instant,
+    started_at: SystemTime,
+    writer: Mutex<TraceWriter<W>>,
+}
+
+struct TraceWriter<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+/// Reads the records of a trace written by a `TraceRecorder`.
+pub struct TraceReader<R> {
+    reader: R,
+    started_at: SystemTime,
+}
+
+/// A single recorded call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceRecord {
+    /// The time at which the call completed, relative to the start of the recording.
+    pub elapsed: Duration,
+    pub call: TraceCall,
+    pub result: TraceResult,
+}
+
+/// A call to one of the `LaserBackend` functions, along with its arguments.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceCall {
+    BeyondExeStarted,
+    BeyondExeReady,
+    EnableLaserOutput,
+    DisableLaserOutput,
+    Blackout,
+    GetDllVersion,
+    GetBeyondVersion,
+    GetProjectorCount,
+    GetZoneCount,
+    InsertZoneImage { zone_index: i32, image_name: ImageName },
+    InsertProjectorImage { projector_index: i32, image_name: ImageName },
+    DeleteZoneImage { image_name: ImageName },
+    DeleteProjectorImage { image_name: ImageName },
+    SendFrame {
+        image_name: ImageName,
+        points: Vec<BeyondLaserPoint>,
+        zones: ZoneSet,
+        /// The raw `ARate` argument.
+        scan_rate: i32,
+    },
+}
+
+/// The result of a recorded call.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TraceResult {
+    /// The call succeeded. Holds the returned count, 1 for `true` and success, or 0 for `false`.
+    Ok(i32),
+    /// The SDK function returned the given failure or undocumented code.
+    Failed(i32),
+    /// The call failed without reaching the SDK function, e.g. as it is unsupported.
+    Error,
+}
+
+/// The outcome of replaying a trace.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// The number of calls replayed.
+    pub calls: usize,
+    /// The number of calls whose result differed from the recorded result.
+    pub mismatched: usize,
+}
+
+impl<B: LaserBackend, W: Write> TraceRecorder<B, W> {
+    /// Begin recording calls to `backend` into `writer`.
+    ///
+    /// Consider wrapping `writer` in a `BufWriter`, as each frame is written in full. The writer is
+    /// flushed after each record, so the trace is complete up to the last call even if the
+    /// process is killed before `finish`.
+    pub fn new(backend: B, mut writer: W) -> Result<Self> {
+        let start = Instant::now();
+        let started_at = SystemTime::now();
+        write_header(&mut writer, started_at).map_err(Error::Trace)?;
+        let writer = TraceWriter { writer, error: None };
+        Ok(TraceRecorder { backend, start, started_at, writer: Mutex::new(writer) })
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// The wall-clock time at which recording began.
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// Flush and return the writer along with the wrapped backend, or the first error that
+    /// occurred while writing the trace.
+    pub fn finish(self) -> Result<(B, W)> {
+        let TraceWriter { mut writer, error } = self.writer.into_inner().unwrap();
+        if let Some(err) = error {
+            return Err(Error::Trace(err));
+        }
+        writer.flush().map_err(Error::Trace)?;
+        Ok((self.backend, writer))
+    }
+
+    fn record<T: TraceValue>(&self, call: TraceCall, result: Result<T>) -> Result<T> {
+        let record = TraceRecord {
+            elapsed: self.start.elapsed(),
+            call,
+            result: TraceResult::from_result(&result),
+        };
+        let mut writer = self.writer.lock().unwrap();
+        if writer.error.is_none() {
+            let written = write_record(&mut writer.writer, &record)
+                .and_then(|()| writer.writer.flush());
+            if let Err(err) = written {
+                writer.error = Some(err);
+            }
+        }
+        result
+    }
+}
+
+impl<B: LaserBackend, W: Write> LaserBackend for TraceRecorder<B, W> {
+    fn beyond_exe_started(&self) -> Result<bool> {
+        self.record(TraceCall::BeyondExeStarted, self.backend.beyond_exe_started())
+    }
+
+    fn beyond_exe_ready(&self) -> Result<bool> {
+        self.record(TraceCall::BeyondExeReady, self.backend.beyond_exe_ready())
+    }
+
+    fn enable_laser_output(&self) -> Result<()> {
+        self.record(TraceCall::EnableLaserOutput, self.backend.enable_laser_output())
+    }
+
+    fn disable_laser_output(&self) -> Result<()> {
+        self.record(TraceCall::DisableLaserOutput, self.backend.disable_laser_output())
+    }
+
+    fn blackout(&self) -> Result<()> {
+        self.record(TraceCall::Blackout, self.backend.blackout())
+    }
+
+    fn get_dll_version(&self) -> Result<i32> {
+        self.record(TraceCall::GetDllVersion, self.backend.get_dll_version())
+    }
+
+    fn get_beyond_version(&self) -> Result<i32> {
+        self.record(TraceCall::GetBeyondVersion, self.backend.get_beyond_version())
+    }
+
+    fn get_projector_count(&self) -> Result<i32> {
+        self.record(TraceCall::GetProjectorCount, self.backend.get_projector_count())
+    }
+
+    fn get_zone_count(&self) -> Result<i32> {
+        self.record(TraceCall::GetZoneCount, self.backend.get_zone_count())
+    }
+
+    fn insert_zone_image(&self, zone_index: i32, image_name: &ImageName) -> Result<()> {
+        let result = self.backend.insert_zone_image(zone_index, image_name);
+        let call = TraceCall::InsertZoneImage { zone_index, image_name: image_name.clone() };
+        self.record(call, result)
+    }
+
+    fn insert_projector_image(&self, projector_index: i32, image_name: &ImageName) -> Result<()> {
+        let result = self.backend.insert_projector_image(projector_index, image_name);
+        let call = TraceCall::InsertProjectorImage {
+            projector_index,
+            image_name: image_name.clone(),
+        };
+        self.record(call, result)
+    }
+
+    fn delete_zone_image(&self, image_name: &ImageName) -> Result<()> {
+        let result = self.backend.delete_zone_image(image_name);
+        self.record(TraceCall::DeleteZoneImage { image_name: image_name.clone() }, result)
+    }
+
+    fn delete_projector_image(&self, image_name: &ImageName) -> Result<()> {
+        let result = self.backend.delete_projector_image(image_name);
+        self.record(TraceCall::DeleteProjectorImage { image_name: image_name.clone() }, result)
+    }
+
+    fn send_frame(&self,
+                  image_name: &ImageName,
+                  laser_points: &[BeyondLaserPoint],
+                  zone_array: &ZoneArray,
+                  scan_rate: i32) -> Result<()>
+    {
+        let result = self.backend.send_frame(image_name, laser_points, zone_array, scan_rate);
+        let zones = ZoneSet::from_indices(&zone_array.zone_indices())
+            .expect("zone arrays are only built from valid zone sets");
+        let call = TraceCall::SendFrame {
+            image_name: image_name.clone(),
+            points: laser_points.to_vec(),
+            zones,
+            scan_rate,
+        };
+        self.record(call, result)
+    }
+}
+
+impl<R: Read> TraceReader<R> {
+    /// Returns an error if `reader` does not begin with a trace header.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let started_at = read_header(&mut reader).map_err(Error::Trace)?;
+        Ok(TraceReader { reader, started_at })
+    }
+
+    /// The wall-clock time at which recording began. Add a record's `elapsed` time to find the
+    /// time at which its call completed.
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// The next record, or `None` at the end of the trace.
+    pub fn next_record(&mut self) -> Result<Option<TraceRecord>> {
+        read_record(&mut self.reader).map_err(Error::Trace)
+    }
+}
+
+impl<R: Read> Iterator for TraceReader<R> {
+    type Item = Result<TraceRecord>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_record() {
+            Ok(record) => record.map(Ok),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl TraceRecord {
+    /// Make the recorded call against the given backend, returning its result.
+    pub fn replay<B: ?Sized + LaserBackend>(&self, backend: &B) -> TraceResult {
+        match self.call {
+            TraceCall::BeyondExeStarted => TraceResult::from_result(&backend.beyond_exe_started()),
+            TraceCall::BeyondExeReady => TraceResult::from_result(&backend.beyond_exe_ready()),
+            TraceCall::EnableLaserOutput =>
+                TraceResult::from_result(&backend.enable_laser_output()),
+            TraceCall::DisableLaserOutput =>
+                TraceResult::from_result(&backend.disable_laser_output()),
+            TraceCall::Blackout => TraceResult::from_result(&backend.blackout()),
+            TraceCall::GetDllVersion => TraceResult::from_result(&backend.get_dll_version()),
+            TraceCall::GetBeyondVersion => TraceResult::from_result(&backend.get_beyond_version()),
+            TraceCall::GetProjectorCount =>
+                TraceResult::from_result(&backend.get_projector_count()),
+            TraceCall::GetZoneCount => TraceResult::from_result(&backend.get_zone_count()),
+            TraceCall::InsertZoneImage { zone_index, ref image_name } =>
+                TraceResult::from_result(&backend.insert_zone_image(zone_index, image_name)),
+            TraceCall::InsertProjectorImage { projector_index, ref image_name } => {
+                let result = backend.insert_projector_image(projector_index, image_name);
+                TraceResult::from_result(&result)
+            },
+            TraceCall::DeleteZoneImage { ref image_name } =>
+                TraceResult::from_result(&backend.delete_zone_image(image_name)),
+            TraceCall::DeleteProjectorImage { ref image_name } =>
+                TraceResult::from_result(&backend.delete_projector_image(image_name)),
+            TraceCall::SendFrame { ref image_name, ref points, ref zones, scan_rate } => {
+                let zone_array = ZoneArray::new(zones);
+                let result = backend.send_frame(image_name, points, &zone_array, scan_rate);
+                TraceResult::from_result(&result)
+            },
+        }
+    }
+}
+
+/// Replay every call within the trace against the given backend.
+///
+/// If `realtime` is `true`, each call is delayed until the same time after the start of the
+/// replay as it was recorded at.
+pub fn replay<R, B>(reader: TraceReader<R>, backend: &B, realtime: bool) -> Result<ReplayStats>
+    where R: Read,
+          B: ?Sized + LaserBackend,
+{
+    let start = Instant::now();
+    let mut stats = ReplayStats::default();
+    for record in reader {
+        let record = record?;
+        if realtime {
+            let elapsed = start.elapsed();
+            if record.elapsed > elapsed {
+                thread::sleep(record.elapsed - elapsed);
+            }
+        }
+        if record.replay(backend) != record.result {
+            stats.mismatched += 1;
+        }
+        stats.calls += 1;
+    }
+    Ok(stats)
+}
+
+/// The successful values returned by `LaserBackend` functions.
+trait TraceValue {
+    fn to_code(&self) -> i32;
+}
+
+impl TraceValue for () {
+    fn to_code(&self) -> i32 {
+        1
+    }
+}
+
+impl TraceValue for bool {
+    fn to_code(&self) -> i32 {
+        *self as i32
+    }
+}
+
+impl TraceValue for i32 {
+    fn to_code(&self) -> i32 {
+        *self
+    }
+}
+
+impl TraceResult {
+    fn from_result<T: TraceValue>(result: &Result<T>) -> Self {
+        match *result {
+            Ok(ref value) => TraceResult::Ok(value.to_code()),
+            Err(Error::Failed { code, .. }) | Err(Error::UnexpectedReturn { code, .. }) =>
+                TraceResult::Failed(code),
+            Err(_) => TraceResult::Error,
+        }
+    }
+}
+
+// Encoding. All integers are little-endian.
+
+fn write_header<W: Write>(w: &mut W, started_at: SystemTime) -> io::Result<()> {
+    let since_epoch = started_at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    w.write_all(MAGIC)?;
+    w.write_all(&(since_epoch.as_micros() as u64).to_le_bytes())
+}
+
+fn read_header<R: Read>(r: &mut R) -> io::Result<SystemTime> {
+    let mut magic = [0u8; 8];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a Pangolin trace"));
+    }
+    let mut micros = [0u8; 8];
+    r.read_exact(&mut micros)?;
+    Ok(UNIX_EPOCH + Duration::from_micros(u64::from_le_bytes(micros)))
+}
+
+fn write_record<W: Write>(w: &mut W, record: &TraceRecord) -> io::Result<()> {
+    let micros = record.elapsed.as_secs() * 1_000_000 + record.elapsed.subsec_micros() as u64;
+    w.write_all(&micros.to_le_bytes())?;
+    let (tag, code) = match record.result {
+        TraceResult::Ok(code) => (0u8, code),
+        TraceResult::Failed(code) => (1, code),
+        TraceResult::Error => (2, 0),
+    };
+    w.write_all(&[tag])?;
+    w.write_all(&code.to_le_bytes())?;
+    match record.call {
+        TraceCall::BeyondExeStarted => w.write_all(&[0]),
+        TraceCall::BeyondExeReady => w.write_all(&[1]),
+        TraceCall::EnableLaserOutput => w.write_all(&[2]),
+        TraceCall::DisableLaserOutput => w.write_all(&[3]),
+        TraceCall::Blackout => w.write_all(&[4]),
+        TraceCall::GetDllVersion => w.write_all(&[5]),
+        TraceCall::GetBeyondVersion => w.write_all(&[6]),
+        TraceCall::GetProjectorCount => w.write_all(&[7]),
+        TraceCall::GetZoneCount => w.write_all(&[8]),
+        TraceCall::InsertZoneImage { zone_index, ref image_name } => {
+            w.write_all(&[9])?;
+            w.write_all(&zone_index.to_le_bytes())?;
+            write_name(w, image_name)
+        },
+        TraceCall::InsertProjectorImage { projector_index, ref image_name } => {
+            w.write_all(&[10])?;
+            w.write_all(&projector_index.to_le_bytes())?;
+            write_name(w, image_name)
+        },
+        TraceCall::DeleteZoneImage { ref image_name } => {
+            w.write_all(&[11])?;
+            write_name(w, image_name)
+        },
+        TraceCall::DeleteProjectorImage { ref image_name } => {
+            w.write_all(&[12])?;
+            write_name(w, image_name)
+        },
+        TraceCall::SendFrame { ref image_name, ref points, ref zones, scan_rate } => {
+            w.write_all(&[13])?;
+            write_name(w, image_name)?;
+            w.write_all(&[zones.len() as u8])?;
+            w.write_all(zones.indices())?;
+            w.write_all(&scan_rate.to_le_bytes())?;
+            w.write_all(&(points.len() as u32).to_le_bytes())?;
+            for point in points {
+                let [x, y, z] = point.position();
+                w.write_all(&x.to_le_bytes())?;
+                w.write_all(&y.to_le_bytes())?;
+                w.write_all(&z.to_le_bytes())?;
+                w.write_all(&point.packed_colour().to_le_bytes())?;
+                w.write_all(&[point.rep_count(), point.focus(), point.status()])?;
+            }
+            Ok(())
+        },
+    }
+}
+
+fn write_name<W: Write>(w: &mut W, image_name: &ImageName) -> io::Result<()> {
+    let bytes = image_name.as_str().as_bytes();
+    if bytes.len() > u32::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "image name is too long to trace"));
+    }
+    w.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    w.write_all(bytes)
+}
+
+/// Returns `None` if the trace ends cleanly before the next record.
+fn read_record<R: Read>(r: &mut R) -> io::Result<Option<TraceRecord>> {
+    let mut micros = [0u8; 8];
+    match r.read(&mut micros[..1])? {
+        0 => return Ok(None),
+        _ => r.read_exact(&mut micros[1..])?,
+    }
+    let micros = u64::from_le_bytes(micros);
+    let elapsed = Duration::from_micros(micros);
+    let tag = read_u8(r)?;
+    let code = read_i32(r)?;
+    let result = match tag {
+        0 => TraceResult::Ok(code),
+        1 => TraceResult::Failed(code),
+        2 => TraceResult::Error,
+        _ => return Err(invalid_data("unknown result tag")),
+    };
+    let call = match read_u8(r)? {
+        0 => TraceCall::BeyondExeStarted,
+        1 => TraceCall::BeyondExeReady,
+        2 => TraceCall::EnableLaserOutput,
+        3 => TraceCall::DisableLaserOutput,
+        4 => TraceCall::Blackout,
+        5 => TraceCall::GetDllVersion,
+        6 => TraceCall::GetBeyondVersion,
+        7 => TraceCall::GetProjectorCount,
+        8 => TraceCall::GetZoneCount,
+        9 => TraceCall::InsertZoneImage { zone_index: read_i32(r)?, image_name: read_name(r)? },
+        10 => TraceCall::InsertProjectorImage {
+            projector_index: read_i32(r)?,
+            image_name: read_name(r)?,
+        },
+        11 => TraceCall::DeleteZoneImage { image_name: read_name(r)? },
+        12 => TraceCall::DeleteProjectorImage { image_name: read_name(r)? },
+        13 => {
+            let image_name = read_name(r)?;
+            let mut indices = vec![0u8; read_u8(r)? as usize];
+            r.read_exact(&mut indices)?;
+            let zones = ZoneSet::from_indices(&indices)
+                .map_err(|_| invalid_data("invalid zone indices"))?;
+            let scan_rate = read_i32(r)?;
+            let count = read_u32(r)? as usize;
+            let mut points = Vec::with_capacity(count.min(MAX_NUM_POINTS));
+            for _ in 0..count {
+                let (x, y, z) = (read_f32(r)?, read_f32(r)?, read_f32(r)?);
+                let colour = read_u32(r)?;
+                let mut point = BeyondLaserPoint::from_packed_colour(x, y, z, colour);
+                point.set_rep_count(read_u8(r)?)
+                    .set_focus(read_u8(r)?)
+                    .set_status(read_u8(r)?);
+                points.push(point);
+            }
+            TraceCall::SendFrame { image_name, points, zones, scan_rate }
+        },
+        _ => return Err(invalid_data("unknown call tag")),
+    };
+    Ok(Some(TraceRecord { elapsed, call, result }))
+}
+
+fn read_name<R: Read>(r: &mut R) -> io::Result<ImageName> {
+    let len = read_u32(r)? as usize;
+    let mut bytes = vec![];
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let name = String::from_utf8(bytes).map_err(|_| invalid_data("invalid image name"))?;
+    ImageName::new(name).map_err(|_| invalid_data("invalid image name"))
+}
+
+fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    let mut bytes = [0u8; 1];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_i32<R: Read>(r: &mut R) -> io::Result<i32> {
+    read_u32(r).map(|u| u as i32)
+}
+
+fn read_f32<R: Read>(r: &mut R) -> io::Result<f32> {
+    read_u32(r).map(f32::from_bits)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[test]
+fn test_trace_round_trip() {
+    use {ScanRate, SimulatedBeyond};
+
+    let output1 = ImageName::new("/output1").unwrap();
+    let zones = ZoneSet::from_indices(&[1, 0]).unwrap();
+    let mut point = BeyondLaserPoint::new(0.25, 0.5, 0.75, 1, 2, 3);
+    point.set_rep_count(4);
+    let points = vec![point; 3];
+
+    // Names longer than a `u16` length prefix could hold must survive the round trip.
+    let long_name = ImageName::new("/".repeat(70_000)).unwrap();
+
+    let before = SystemTime::now();
+    let recorder = TraceRecorder::new(SimulatedBeyond::new(1, 2), vec![]).unwrap();
+    let started_at = recorder.started_at();
+    assert!(started_at >= before && started_at <= SystemTime::now());
+    recorder.insert_zone_image(0, &long_name).unwrap();
+    recorder.insert_zone_image(0, &output1).unwrap();
+    recorder.send_frame_to_image(&output1, &points, &zones, ScanRate::default()).unwrap();
+    assert!(recorder.insert_zone_image(0, &ImageName::new("/x").unwrap()).is_ok());
+    let (_, trace) = recorder.finish().unwrap();
+
+    let reader = TraceReader::new(&trace[..]).unwrap();
+    let started_at_micros = |time: SystemTime| time.duration_since(UNIX_EPOCH).unwrap().as_micros();
+    assert_eq!(started_at_micros(reader.started_at()), started_at_micros(started_at));
+    let records: Vec<_> = reader.map(|r| r.unwrap()).collect();
+    let calls: Vec<_> = records.iter().map(|record| record.call.clone()).collect();
+    assert_eq!(calls[0], TraceCall::InsertZoneImage { zone_index: 0, image_name: long_name });
+    assert_eq!(calls[1], TraceCall::InsertZoneImage { zone_index: 0, image_name: output1.clone() });
+    assert_eq!(calls[2], TraceCall::SendFrame {
+        image_name: output1.clone(),
+        points: points.clone(),
+        zones: zones.clone(),
+        scan_rate: 100,
+    });
+    assert_eq!(records.len(), 4);
+
+    // Replaying into a fresh instance reproduces the same frames and results.
+    let beyond = SimulatedBeyond::new(1, 2);
+    let stats = replay(TraceReader::new(&trace[..]).unwrap(), &beyond, false).unwrap();
+    assert_eq!(stats, ReplayStats { calls: 4, mismatched: 0 });
+    let frame = beyond.image_frame(&output1).unwrap();
+    assert_eq!(frame.points, points);
+    assert_eq!(frame.zone_indices, vec![1, 0]);
+
+    assert!(TraceReader::new(&b"not a trace"[..]).is_err());
+}
@@ -0,0 +1,145 @@
+use {Error, LaserBackend, Result};
+
+/// The maximum number of projection zones supported by Beyond.
+pub const MAX_ZONES: u8 = 200;
+
+/// An ordered set of zero-based projection zone indices to which a frame is sent.
+///
+/// The order in which zones are inserted is preserved, as Beyond uses it for time-shift
+/// calculations. Each zone may appear at most once and must be less than `MAX_ZONES`. Whether the
+/// zones exist in the running instance of Beyond is checked by `validate`, or against a cached
+/// zone count by `Image::send_frame`, rather than on every frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ZoneSet {
+    indices: Vec<u8>,
+}
+
+/// The 0-terminated array of zone indices passed to `ldbSendFrameToImage`.
+///
+/// Beyond may have up to 200 projection zones which don't fit in a bitmask, so the SDK expects an
+/// array of 256 bytes containing a sequence of zone indices counting from 1. A value of 0 marks the
+/// end of the sequence. The order is significant as Beyond uses it for time-shift calculations.
+#[derive(Copy, Clone)]
+pub struct ZoneArray {
+    array: [u8; 256],
+}
+
+impl ZoneSet {
+    /// An empty set of zones.
+    pub fn new() -> Self {
+        ZoneSet { indices: vec![] }
+    }
+
+    /// A set containing only the given zone.
+    pub fn single(zone_index: u8) -> Result<Self> {
+        ZoneSet::from_indices(&[zone_index])
+    }
+
+    /// A set containing the given zones in order.
+    ///
+    /// Returns an error if any zone appears more than once or is not less than `MAX_ZONES`.
+    pub fn from_indices(zone_indices: &[u8]) -> Result<Self> {
+        let mut zones = ZoneSet::new();
+        for &zone_index in zone_indices {
+            zones.insert(zone_index)?;
+        }
+        Ok(zones)
+    }
+
+    /// Append a zone to the end of the set.
+    ///
+    /// Returns an error if the zone is already within the set or is not less than `MAX_ZONES`.
+    pub fn insert(&mut self, zone_index: u8) -> Result<&mut Self> {
+        if zone_index >= MAX_ZONES {
+            return Err(Error::InvalidZoneIndex { index: zone_index, zone_count: MAX_ZONES as i32 });
+        }
+        if self.contains(zone_index) {
+            return Err(Error::DuplicateZoneIndex(zone_index));
+        }
+        self.indices.push(zone_index);
+        Ok(self)
+    }
+
+    pub fn contains(&self, zone_index: u8) -> bool {
+        self.indices.contains(&zone_index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// The zero-based zone indices in insertion order.
+    pub fn indices(&self) -> &[u8] {
+        &self.indices
+    }
+
+    /// Returns an error for the first zone that the given Beyond does not have.
+    ///
+    /// This queries the zone count once, so prefer to validate a set when it is created rather than
+    /// before every frame.
+    pub fn validate<B: ?Sized + LaserBackend>(&self, backend: &B) -> Result<()> {
+        self.check_zone_count(backend.get_zone_count()?)
+    }
+
+    /// Returns an error for the first zone that is not less than `zone_count`.
+    pub fn check_zone_count(&self, zone_count: i32) -> Result<()> {
+        match self.indices.iter().find(|&&i| i as i32 >= zone_count) {
+            Some(&index) => Err(Error::InvalidZoneIndex { index, zone_count }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ZoneArray {
+    /// Encode the given zones.
+    pub fn new(zones: &ZoneSet) -> Self {
+        let mut array = [0u8; 256];
+        for (elem, &zone_index) in array.iter_mut().zip(zones.indices()) {
+            *elem = 1 + zone_index;
+        }
+        // Last elem is indicated by a trailing 0, which `MAX_ZONES` always leaves room for.
+        ZoneArray { array }
+    }
+
+    /// Decode the zero-based zone indices in order, stopping at the terminating 0.
+    pub fn zone_indices(&self) -> Vec<u8> {
+        self.array.iter()
+            .take_while(|&&i| i != 0)
+            .map(|&i| i - 1)
+            .collect()
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.array.as_ptr()
+    }
+}
+
+#[test]
+fn test_zone_set() {
+    use SimulatedBeyond;
+
+    let mut zones = ZoneSet::new();
+    zones.insert(3).unwrap().insert(0).unwrap().insert(MAX_ZONES - 1).unwrap();
+    assert_eq!(zones.indices(), &[3, 0, MAX_ZONES - 1]);
+    assert!(zones.insert(3).is_err());
+    assert!(zones.insert(MAX_ZONES).is_err());
+    assert_eq!(zones.len(), 3);
+
+    assert!(zones.check_zone_count(MAX_ZONES as i32).is_ok());
+    assert!(zones.check_zone_count(4).is_err());
+
+    assert!(zones.validate(&SimulatedBeyond::new(1, MAX_ZONES as i32)).is_ok());
+    match zones.validate(&SimulatedBeyond::new(1, 4)) {
+        Err(Error::InvalidZoneIndex { index, zone_count: 4 }) => assert_eq!(index, MAX_ZONES - 1),
+        result => panic!("expected an invalid zone index, found {:?}", result),
+    }
+
+    let all: Vec<u8> = (0..MAX_ZONES).collect();
+    let zones = ZoneSet::from_indices(&all).unwrap();
+    assert_eq!(ZoneArray::new(&zones).zone_indices(), all);
+    assert!(ZoneArray::new(&ZoneSet::new()).zone_indices().is_empty());
+}
@@ -1,10 +1,18 @@
 extern crate pangolin;
 extern crate rosc;
 
-use pangolin::{Pangolin, BeyondLaserPoint};
+use pangolin::{BeyondLaserPoint, ConnectionEvent, ConnectionState, FrameSubmitter, ImageName,
+               LaserBackend, Pangolin, ProjectorTick, ScanRate, SubmitReport, Supervisor, TimerTick,
+               TraceRecorder, ZoneSet};
 use rosc::{OscPacket, OscType};
 use std::collections::HashMap;
-use std::sync::mpsc;
+use std::fs::File;
+use std::io::BufWriter;
+use std::net::{SocketAddr, UdpSocket};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 
 type BlobChunks = Vec<Vec<u8>>;
 
@@ -26,15 +34,106 @@ struct LayerFrame {
 // have 5 outputs.
 const NUM_OUTPUTS: usize = 5;
 
+// The interval at which frames are submitted when no projector event is available.
+const FRAME_INTERVAL: Duration = Duration::from_millis(5);
 
-/// We run the OSC receiver on a separate thread.
+// The longest we wait for a projector tick before checking for new frames anyway, e.g. while
+// Beyond is not running.
+const TICK_TIMEOUT: Duration = Duration::from_millis(50);
+
+// The address on which OSC packets are received when no `--listen` address is given.
+const DEFAULT_LISTEN_ADDR: &str = "0.0.0.0:9001";
+
+/// Options given on the command line.
+struct Config {
+    /// The scan rate of each output, defaulting to 100% of the projector's scan rate.
+    scan_rates: [ScanRate; NUM_OUTPUTS],
+    /// The addresses on which to receive OSC packets, defaulting to `DEFAULT_LISTEN_ADDR`.
+    listen_addrs: Vec<SocketAddr>,
+    /// The file to record every call made to Beyond in, if any.
+    trace_path: Option<PathBuf>,
+}
+
+const USAGE: &str = "usage: osc_to_beyond [--scan-rate OUTPUT=RATE]... [--listen ADDR]... [--trace PATH]
+
+    --scan-rate OUTPUT=RATE    Scan output OUTPUT (1 to 5) at RATE, either a percentage of
+                               the projector's scan rate (e.g. 80%) or points per second
+                               (e.g. 30000pps)
+    --listen ADDR              Receive OSC packets on the UDP address ADDR, e.g. 0.0.0.0:9001
+                               or [::]:9001. May be given more than once to listen on several
+                               addresses. Defaults to 0.0.0.0:9001
+    --trace PATH               Record every call made to Beyond in the file at PATH, replacing
+                               it if it exists";
+
+impl Config {
+    /// Parse the config from the given command line arguments, excluding the program name.
+    fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut config = Config {
+            scan_rates: [ScanRate::default(); NUM_OUTPUTS],
+            listen_addrs: vec![],
+            trace_path: None,
+        };
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--scan-rate" => {
+                    let value = args.next().ok_or("--scan-rate requires a value")?;
+                    let (output, rate) = parse_output_scan_rate(&value)?;
+                    config.scan_rates[output] = rate;
+                },
+                "--listen" => {
+                    let value = args.next().ok_or("--listen requires a value")?;
+                    let addr = value.trim().parse::<SocketAddr>().map_err(|err| {
+                        format!("invalid listen address {:?}: {}", value, err)
+                    })?;
+                    if !config.listen_addrs.contains(&addr) {
+                        config.listen_addrs.push(addr);
+                    }
+                },
+                "--trace" => {
+                    let value = args.next().ok_or("--trace requires a value")?;
+                    config.trace_path = Some(PathBuf::from(value));
+                },
+                _ => return Err(format!("unexpected argument {:?}", arg)),
+            }
+        }
+        if config.listen_addrs.is_empty() {
+            config.listen_addrs.push(DEFAULT_LISTEN_ADDR.parse().expect("valid default address"));
+        }
+        Ok(config)
+    }
+}
+
+/// Bind a UDP socket to each of the given addresses, failing on the first that can't be bound.
+fn bind_osc_sockets(addrs: &[SocketAddr]) -> Result<Vec<UdpSocket>, String> {
+    addrs.iter()
+        .map(|addr| {
+            UdpSocket::bind(addr)
+                .map_err(|err| format!("Failed to listen for OSC on {}: {}", addr, err))
+        })
+        .collect()
+}
+
+/// Parse an `OUTPUT=RATE` pair into a zero-based output index and its scan rate.
+fn parse_output_scan_rate(value: &str) -> Result<(usize, ScanRate), String> {
+    let mut parts = value.splitn(2, '=');
+    let output = parts.next().unwrap_or("");
+    let rate = parts.next().ok_or_else(|| format!("expected OUTPUT=RATE, found {:?}", value))?;
+    let output = match output.trim().parse::<usize>() {
+        Ok(output) if (1..=NUM_OUTPUTS).contains(&output) => output - 1,
+        _ => return Err(format!("invalid output {:?}: must be 1 to {}", output, NUM_OUTPUTS)),
+    };
+    let rate = rate.parse::<ScanRate>().map_err(|err| err.to_string())?;
+    Ok((output, rate))
+}
+
+/// We run an OSC receiver on a separate thread for each socket.
 ///
-/// The receiver receives packets as fast as possible, updates the `Layer` map and sends new frames
-/// to the main thread when available.
-fn run_osc(frame_sender: mpsc::Sender<LayerFrame>) {
+/// Each receiver receives packets as fast as possible, updates its `Layer` map and sends new
+/// frames to the main thread when available.
+fn run_osc(sockets: Vec<UdpSocket>, frame_sender: mpsc::Sender<LayerFrame>) {
     use std::sync::atomic::Ordering;
 
-    let received_packet = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let received_packet = Arc::new(AtomicBool::new(true));
     let received_packet_clone = received_packet.clone();
     let frame_sender_clone = frame_sender.clone();
 
@@ -70,8 +169,19 @@ fn run_osc(frame_sender: mpsc::Sender<LayerFrame>) {
         }
     });
 
-    // Listen for packets on 9001.
-    let osc_socket = std::net::UdpSocket::bind("0.0.0.0:9001").unwrap();
+    for osc_socket in sockets {
+        let frame_sender = frame_sender.clone();
+        let received_packet = received_packet.clone();
+        std::thread::spawn(move || receive_osc(osc_socket, frame_sender, received_packet));
+    }
+}
+
+/// Receive OSC packets on the given socket until the frame channel closes.
+fn receive_osc(osc_socket: UdpSocket,
+               frame_sender: mpsc::Sender<LayerFrame>,
+               received_packet: Arc<AtomicBool>)
+{
+    use std::sync::atomic::Ordering;
 
     // Re-use a buffer for receiving and decoding OSC via UDP packets.
     let mut osc_buffer = [0u8; 64_000];
@@ -155,14 +265,14 @@ fn run_osc(frame_sender: mpsc::Sender<LayerFrame>) {
 
         {
             // The layer at the given address, e.g. `/layer1`, `/layer2` or `/layer3`.
-            let mut layer = layer_map.entry(message.addr.clone()).or_insert(Layer::default());
+            let layer = layer_map.entry(message.addr.clone()).or_insert(Layer::default());
 
             // Get the length of the blob in case we need to allocate a `Vec` for a new frame.
             let blob_len = blob.len();
 
             // Append the received blob to the layer at the given time stamp.
             {
-                let mut blob_chunks = layer.blob_map.entry(time_stamp).or_insert(Vec::new());
+                let blob_chunks = layer.blob_map.entry(time_stamp).or_insert(Vec::new());
                 blob_chunks.push(blob);
             }
 
@@ -200,9 +310,9 @@ fn run_osc(frame_sender: mpsc::Sender<LayerFrame>) {
 
                 // Send the frame to the main pangolin thread.
                 let layer_frame = LayerFrame {
-                    frame: frame,
+                    frame,
                     layer: message.addr,
-                    outputs: outputs,
+                    outputs,
                 };
 
                 // If the channel is closed, assume we are finished and exit the osc loop.
@@ -236,38 +346,127 @@ fn run_osc(frame_sender: mpsc::Sender<LayerFrame>) {
 
 
 fn main() {
-    let lib = pangolin::load_library().unwrap();
-    let pangolin = Pangolin::new(&lib).unwrap();
-
-    println!("
-        Beyond Exe Started = {}
-        Beyond Exe Ready = {}
-        Beyond App Version = {}
-        Beyond DLL Version = {}
-        Beyond Projection Count = {}
-        Beyond Zone Count = {}
-    ", pangolin.beyond_exe_started(),
-       pangolin.beyond_exe_ready(),
-       pangolin.get_beyond_version(),
-       pangolin.get_dll_version(),
-       pangolin.get_projector_count(),
-       pangolin.get_zone_count());
-
-    pangolin.create_zone_image(0, b"/output1\0");
-    pangolin.create_zone_image(1, b"/output2\0");
-    pangolin.create_zone_image(2, b"/output3\0");
-    pangolin.create_zone_image(3, b"/output4\0");
-    pangolin.create_zone_image(4, b"/output5\0");
-
-    // Ask Beyonod to enable the laser output.
-    pangolin.enable_laser_output();
-
-    // Spawn the OSC receiving thread.
-    let (frame_sender, frame_receiver) = mpsc::channel();
-    std::thread::spawn(move || run_osc(frame_sender));
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(err) => {
+            println!("{}\n\n{}", err, USAGE);
+            std::process::exit(1);
+        },
+    };
+
+    // Bind up front so that an address in use is reported before anything else starts.
+    let osc_sockets = match bind_osc_sockets(&config.listen_addrs) {
+        Ok(sockets) => sockets,
+        Err(err) => {
+            println!("{}", err);
+            std::process::exit(1);
+        },
+    };
+    for addr in &config.listen_addrs {
+        println!("Listening for OSC on {}", addr);
+    }
+
+    let pangolin = match Pangolin::load() {
+        Ok(pangolin) => Arc::new(pangolin),
+        Err(err) => {
+            println!("Failed to initialise Pangolin: {}", err);
+            std::process::exit(1);
+        },
+    };
+
+    // Older builds of BEYONDIO.dll lack some of the optional SDK functions.
+    println!("Beyond SDK capabilities: {:?}", pangolin.capabilities());
+
+    // Submit frames as soon as Beyond has taken a copy of the previous ones, falling back to a
+    // timer if the projector event is unavailable.
+    #[cfg(windows)]
+    {
+        match pangolin.projector_event(0) {
+            Ok(event) => return run_traced(&pangolin, &event, &config, osc_sockets),
+            Err(err) => println!("Failed to get projector event, using a timer instead: {}", err),
+        }
+    }
+    run_traced(&pangolin, &TimerTick::new(FRAME_INTERVAL), &config, osc_sockets);
+}
+
+/// As `run`, but records every call made to Beyond if a `--trace` path was given.
+fn run_traced<T>(pangolin: &Arc<Pangolin>, tick: &T, config: &Config, osc_sockets: Vec<UdpSocket>)
+    where T: ProjectorTick,
+{
+    let path = match config.trace_path {
+        Some(ref path) => path,
+        None => return run(pangolin.clone(), tick, config, osc_sockets),
+    };
+    let recorder = File::create(path)
+        .map_err(pangolin::Error::Trace)
+        .and_then(|file| TraceRecorder::new(pangolin.clone(), BufWriter::new(file)));
+    match recorder {
+        Ok(recorder) => {
+            println!("Recording calls to Beyond in {}", path.display());
+            run(Arc::new(recorder), tick, config, osc_sockets);
+        },
+        Err(err) => {
+            println!("Failed to create trace {}: {}", path.display(), err);
+            std::process::exit(1);
+        },
+    }
+}
+
+/// Block until the next projector tick, or for at most `TICK_TIMEOUT`.
+fn wait_for_tick<T: ProjectorTick>(tick: &T) {
+    if let Err(err) = tick.wait(TICK_TIMEOUT) {
+        println!("Failed to wait for projector tick: {}", err);
+        std::thread::sleep(TICK_TIMEOUT);
+    }
+}
+
+/// Report a change in the connection to Beyond.
+fn log_connection_event(event: &ConnectionEvent) {
+    match *event {
+        ConnectionEvent::StateChanged(state) => println!("Beyond connection: {:?}", state),
+        ConnectionEvent::ImageFailed { ref name, ref error } =>
+            println!("Failed to create zone image {}: {}", name, error),
+        ConnectionEvent::EnableFailed(ref error) =>
+            println!("Failed to enable laser output: {}", error),
+        ConnectionEvent::QueryFailed(ref error) =>
+            println!("Failed to check if Beyond is ready: {}", error),
+        ConnectionEvent::ArmRefused(ref reason) =>
+            println!("Refusing to arm outputs until Beyond is reconfigured: {}", reason),
+    }
+}
 
-    // Send frames to beyond roughly 60 times per second.
-    let sleep_interval = std::time::Duration::from_millis(5);
+/// Streams the frames received over OSC to the given laser backend, submitting after each tick.
+///
+/// This never returns under normal operation.
+fn run<B, T>(pangolin: Arc<B>, tick: &T, config: &Config, osc_sockets: Vec<UdpSocket>)
+    where B: 'static + LaserBackend + Send + Sync,
+          T: ProjectorTick,
+{
+    match pangolin.beyond_info() {
+        Ok(info) => println!("{}", info),
+        Err(err) => println!("Failed to query Beyond: {}", err),
+    }
+
+    // The zones that each output's frames are addressed to.
+    let output_zones: Vec<ZoneSet> = (0..NUM_OUTPUTS)
+        .map(|i| ZoneSet::single((i + 1) as u8).expect("output zone within MAX_ZONES"))
+        .collect();
+
+    // Supervise an SDK image for each output, named `/output1`, `/output2` and so on.
+    // The images are created and the laser output enabled each time Beyond becomes ready, as
+    // long as it has every zone that the images are created within and send frames to.
+    let mut supervisor = Supervisor::new(&*pangolin);
+    let connection_events = supervisor.subscribe();
+    let images: Vec<usize> = (0..NUM_OUTPUTS)
+        .map(|i| {
+            let name = ImageName::new(format!("/output{}", i + 1)).expect("valid image name");
+            supervisor.add_zone_image(i as i32, name, output_zones[i].clone())
+        })
+        .collect();
+
+    // Spawn the OSC receiving threads.
+    let (frame_sender, frame_receiver) = mpsc::channel();
+    run_osc(osc_sockets, frame_sender);
 
     // Track the most recently received frame per layer.
     let mut layer_frames = HashMap::new();
@@ -275,6 +474,12 @@ fn main() {
     // A frame for each output.
     let mut output_frames = vec![vec![]; NUM_OUTPUTS];
 
+    // Frames are sent from a background thread so that a slow send doesn't block this thread.
+    let submitter = FrameSubmitter::new(pangolin.clone());
+
+    // Whether the last frame sent to each image failed, so that failures are reported once.
+    let mut image_failed = HashMap::new();
+
     loop {
         // Receive pending `LayerFrame`s, sent from the OSC receiver thread.
         for LayerFrame { frame, layer, outputs } in frame_receiver.try_iter() {
@@ -282,8 +487,12 @@ fn main() {
         }
 
         // If Pangolin isn't ready there's nothing more to do.
-        if !pangolin.beyond_exe_ready() {
-            std::thread::sleep(sleep_interval);
+        let state = supervisor.poll();
+        for event in connection_events.try_iter() {
+            log_connection_event(&event);
+        }
+        if state != ConnectionState::Ready {
+            wait_for_tick(tick);
             continue;
         }
 
@@ -293,30 +502,130 @@ fn main() {
         }
 
         // Fill each output from the layers that target it.
-        for &(ref frame, ref outputs) in layer_frames.values() {
+        for (frame, outputs) in layer_frames.values() {
             for &out in outputs {
                 let output = &mut output_frames[out as usize];
-                for &point in frame {
+                for &point in frame.iter() {
                     output.push(point);
                 }
             }
         }
 
-        // Send each output frame to Pangolin.
-        for (i, frame) in output_frames.iter().enumerate() {
-            let address = match i {
-                0 => b"/output1\0",
-                1 => b"/output2\0",
-                2 => b"/output3\0",
-                3 => b"/output4\0",
-                4 => b"/output5\0",
-                _ => unreachable!(),
+        // Submit each output frame to Pangolin, replacing any that haven't been sent yet.
+        for (i, (frame, image)) in output_frames.iter_mut().zip(&images).enumerate() {
+            let image = match supervisor.image(*image) {
+                Some(image) => image,
+                None => continue,
             };
-            let zone_indices = vec![(i+1) as u8];
-            let scan_rate = 100;
-            pangolin.send_frame_to_image(address, frame, &zone_indices, scan_rate);
+            let frame = std::mem::take(frame);
+            submitter.submit(image.name(), frame, output_zones[i].clone(), config.scan_rates[i]);
         }
 
-        std::thread::sleep(sleep_interval);
+        for SubmitReport { image_name, result, .. } in submitter.reports().try_iter() {
+            let failed = image_failed.entry(image_name.clone()).or_insert(false);
+            match result {
+                Ok(()) => *failed = false,
+                Err(err) => {
+                    if !*failed {
+                        println!("Failed to send frame to {}: {}", image_name, err);
+                    }
+                    *failed = true;
+                },
+            }
+        }
+
+        wait_for_tick(tick);
     }
 }
+
+#[test]
+fn test_config_scan_rates() {
+    let parse = |args: &[&str]| Config::from_args(args.iter().map(|arg| arg.to_string()));
+
+    let config = parse(&["--scan-rate", "5=30000pps", "--scan-rate", "1=80%"]).unwrap();
+    assert_eq!(config.scan_rates[0], ScanRate::Percent(80));
+    assert_eq!(config.scan_rates[1], ScanRate::default());
+    assert_eq!(config.scan_rates[4], ScanRate::PointsPerSecond(30_000));
+
+    assert_eq!(parse_output_scan_rate("2=50%"), Ok((1, ScanRate::Percent(50))));
+    assert!(parse_output_scan_rate("6=100%").is_err());
+    assert!(parse_output_scan_rate("0=100%").is_err());
+    assert!(parse_output_scan_rate("1").is_err());
+    assert!(parse_output_scan_rate("1=100").is_err());
+    assert!(parse(&["--scan-rate", "6=100%"]).is_err());
+    assert!(parse(&["--scan-rate", "100%"]).is_err());
+}
+
+#[test]
+fn test_config_listen_addrs() {
+    let parse = |args: &[&str]| Config::from_args(args.iter().map(|arg| arg.to_string()));
+    let addr = |s: &str| s.parse::<SocketAddr>().unwrap();
+
+    let config = parse(&[]).unwrap();
+    assert_eq!(config.listen_addrs, vec![addr(DEFAULT_LISTEN_ADDR)]);
+
+    let config = parse(&["--listen", "[::]:9001"]).unwrap();
+    assert_eq!(config.listen_addrs, vec![addr("[::]:9001")]);
+
+    // Duplicates are only bound once, while distinct addresses are all kept in order.
+    let args = ["--listen", "0.0.0.0:9001", "--listen", "[::]:9001", "--listen", "0.0.0.0:9001"];
+    let config = parse(&args).unwrap();
+    assert_eq!(config.listen_addrs, vec![addr("0.0.0.0:9001"), addr("[::]:9001")]);
+
+    assert!(parse(&["--listen", "9001"]).is_err());
+    assert!(parse(&["--listen", "localhost"]).is_err());
+    assert!(parse(&["--listen"]).is_err());
+}
+
+#[test]
+fn test_config_trace_path() {
+    let parse = |args: &[&str]| Config::from_args(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(parse(&[]).unwrap().trace_path, None);
+    let config = parse(&["--trace", "show.pgtrace", "--listen", "[::]:9001"]).unwrap();
+    assert_eq!(config.trace_path, Some(PathBuf::from("show.pgtrace")));
+    assert!(parse(&["--trace"]).is_err());
+}
+
+#[test]
+fn test_run_streams_osc_frames() {
+    use pangolin::SimulatedBeyond;
+    use rosc::OscMessage;
+    use std::time::Instant;
+
+    let config = Config::from_args(std::iter::empty()).unwrap();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+
+    // Enough zones for each output's image and the zone after it, to which its frames are sent.
+    let beyond = Arc::new(SimulatedBeyond::new(1, NUM_OUTPUTS as i32 + 1));
+    let backend = beyond.clone();
+    std::thread::spawn(move || {
+        run(backend, &TimerTick::new(FRAME_INTERVAL), &config, vec![socket]);
+    });
+
+    // A single point at the centre of x and the bottom of y, with red, green and blue at their
+    // maximum, minimum and midpoint.
+    let blob = vec![0x00, 0x00, 0x00, 0x80, 0x7f, 0x80, 0x00, 0x00];
+    let message = OscMessage {
+        addr: "/layer1".to_string(),
+        args: Some(vec![OscType::Long(1), OscType::Bool(true), OscType::Int(0), OscType::Blob(blob)]),
+    };
+    let packet = rosc::encoder::encode(&OscPacket::Message(message)).unwrap();
+    UdpSocket::bind("127.0.0.1:0").unwrap().send_to(&packet, addr).unwrap();
+
+    let output1 = ImageName::new("/output1").unwrap();
+    let start = Instant::now();
+    let frame = loop {
+        match beyond.image_frame(&output1) {
+            Some(ref frame) if !frame.points.is_empty() => break frame.clone(),
+            _ => {
+                assert!(start.elapsed() < Duration::from_secs(5), "no frame sent to /output1");
+                std::thread::sleep(FRAME_INTERVAL);
+            },
+        }
+    };
+    let x = 32768.0 / 65535.0;
+    assert_eq!(frame.points, vec![BeyondLaserPoint::new(x, 0.0, 0.0, 255, 0, 128)]);
+    assert_eq!(frame.zone_indices, vec![1]);
+}